@@ -0,0 +1,366 @@
+use std::collections::BTreeSet;
+
+use bevy::{
+    log::info,
+    prelude::{
+        BuildChildren, Color, Commands, DespawnRecursiveExt, DetectChanges, Entity, EventReader,
+        EventWriter, Input, IntoSystemAppConfig, IntoSystemConfig, IntoSystemConfigs, KeyCode,
+        NextState, OnEnter, OnExit, OnUpdate, Plugin, Query, Res, ResMut, Resource, StartupSet,
+        Transform, Vec2, Vec3, With,
+    },
+    sprite::{Anchor, Sprite, SpriteBundle},
+    text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    time::{Time, Timer, TimerMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{DisplayAchievements, Toast},
+    config::load_config,
+    constants::{
+        ACHIEVEMENTS_CONFIG_PATH, ACHIEVEMENTS_FILE_NAME, TOAST_SECONDS, TOAST_SLIDE_SECONDS,
+    },
+    events::{RunEnded, RunStats},
+    resource::{StaticAssets, WinSize},
+    state::GameState,
+    storage::Storage,
+};
+
+/// 成就插件
+///
+/// * 监听游戏事件和每一局的统计，按 assets/achievements.ron 中的条件解锁成就
+/// * 解锁时在画面上方弹出提示，菜单中按 A 查看成就列表
+/// * 解锁的成就和累计数据保存在本地
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let config: AchievementConfig = load_config(
+            ACHIEVEMENTS_CONFIG_PATH,
+            include_str!("../assets/achievements.ron"),
+        );
+        app.insert_resource(config)
+            .add_event::<AchievementUnlocked>()
+            .add_startup_system(load_progress_system.in_base_set(StartupSet::PreStartup))
+            .add_systems((count_runs_system, unlock_system, toast_spawn_system).chain())
+            .add_system(toast_system)
+            .add_system(enter_achievements_system.in_set(OnUpdate(GameState::Menu)))
+            .add_system(achievements_display_system.in_schedule(OnEnter(GameState::Achievements)))
+            .add_system(achievements_input_system.in_set(OnUpdate(GameState::Achievements)))
+            .add_system(exit_achievements_system.in_schedule(OnExit(GameState::Achievements)));
+    }
+}
+
+/// 解锁成就事件
+pub struct AchievementUnlocked {
+    pub name: String,
+}
+
+/// 成就配置
+#[derive(Resource, Deserialize)]
+pub struct AchievementConfig {
+    achievements: Vec<Achievement>,
+}
+
+/// 成就
+#[derive(Deserialize)]
+struct Achievement {
+    /// 保存进度时使用的唯一标识
+    id: String,
+    name: String,
+    description: String,
+    condition: Condition,
+}
+
+/// 成就的达成条件
+#[derive(Deserialize, Clone, Copy)]
+enum Condition {
+    /// 单局得分
+    Score(u32),
+    /// 单局连续飞行的次数
+    FlapStreak(u32),
+    /// 单局擦着管道通过的次数
+    NearMisses(u32),
+    /// 累计局数
+    RunsPlayed(u32),
+    /// 不暂停的单局得分
+    ScoreWithoutPause(u32),
+}
+
+impl Condition {
+    fn is_met(&self, run: &RunStats, progress: &AchievementProgress) -> bool {
+        match *self {
+            Condition::Score(score) => run.score >= score,
+            Condition::FlapStreak(flaps) => run.best_flap_streak >= flaps,
+            Condition::NearMisses(near_misses) => run.near_misses >= near_misses,
+            Condition::RunsPlayed(runs) => progress.runs_played >= runs,
+            Condition::ScoreWithoutPause(score) => !run.paused && run.score >= score,
+        }
+    }
+
+    /// 累计类条件的进度，单局条件没有进度
+    fn progress(&self, progress: &AchievementProgress) -> Option<(u32, u32)> {
+        match *self {
+            Condition::RunsPlayed(runs) => Some((progress.runs_played.min(runs), runs)),
+            _ => None,
+        }
+    }
+}
+
+/// 成就进度，保存在本地
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementProgress {
+    /// 已解锁的成就
+    unlocked: BTreeSet<String>,
+    /// 累计局数
+    runs_played: u32,
+}
+
+/// 读取本地保存的成就进度
+fn load_progress_system(mut commands: Commands, storage: Res<Storage>) {
+    let progress: AchievementProgress = storage.load(ACHIEVEMENTS_FILE_NAME);
+    commands.insert_resource(progress);
+}
+
+/// 累计局数
+fn count_runs_system(mut events: EventReader<RunEnded>, mut progress: ResMut<AchievementProgress>) {
+    for event in events.iter() {
+        info!("本局结束 {:?}", event.stats);
+        // 练习不算作一局
+        if !event.stats.practice {
+            progress.runs_played += 1;
+        }
+    }
+}
+
+/// 统计变化时检查并解锁成就，进度变化时保存
+fn unlock_system(
+    config: Res<AchievementConfig>,
+    storage: Res<Storage>,
+    run: Res<RunStats>,
+    mut progress: ResMut<AchievementProgress>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    // 练习的成绩不解锁成就
+    if (!run.is_changed() && !progress.is_changed()) || run.practice {
+        return;
+    }
+    for achievement in config.achievements.iter() {
+        if progress.unlocked.contains(&achievement.id)
+            || !achievement.condition.is_met(&run, &progress)
+        {
+            continue;
+        }
+        info!("解锁成就 {}", achievement.name);
+        progress.unlocked.insert(achievement.id.clone());
+        unlocked_events.send(AchievementUnlocked {
+            name: achievement.name.clone(),
+        });
+    }
+    // 刚读取进度时不需要保存
+    if progress.is_changed() && !progress.is_added() {
+        storage.save(ACHIEVEMENTS_FILE_NAME, &*progress);
+    }
+}
+
+/// 解锁成就时弹出提示，同时解锁多个时依次向下排列
+fn toast_spawn_system(
+    mut commands: Commands,
+    mut events: EventReader<AchievementUnlocked>,
+    static_assets: Res<StaticAssets>,
+    win_size: Res<WinSize>,
+    query: Query<(), With<Toast>>,
+) {
+    let shown = query.iter().count();
+    for (index, event) in (shown..).zip(events.iter()) {
+        spawn_toast(&mut commands, &static_assets, &win_size, &event.name, index);
+    }
+}
+
+/// 在画面上方弹出解锁提示
+fn spawn_toast(
+    commands: &mut Commands,
+    static_assets: &StaticAssets,
+    win_size: &WinSize,
+    name: &str,
+    index: usize,
+) {
+    let font = static_assets.kenney_future_font.clone();
+    let size = Vec2::new(win_size.width - 80., 56.);
+    let y = win_size.height / 2. - 20. - size.y / 2. - index as f32 * (size.y + 8.);
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0., 0., 0., 0.7),
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., y, 30.),
+                ..Default::default()
+            },
+            Toast {
+                y,
+                timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_sections(vec![
+                    TextSection::new(
+                        "ACHIEVEMENT UNLOCKED\r\n".to_owned(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    TextSection::new(
+                        name.to_owned(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 22.,
+                            color: Color::WHITE,
+                        },
+                    ),
+                ])
+                .with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// 提示从上方滑入，显示一段时间后滑出并销毁
+///
+/// * 使用真实时间，暂停时也能正常消失
+fn toast_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    win_size: Res<WinSize>,
+    mut query: Query<(Entity, &mut Toast, &mut Transform)>,
+) {
+    for (entity, mut toast, mut transform) in query.iter_mut() {
+        toast.timer.tick(time.raw_delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let elapsed = toast.timer.elapsed_secs();
+        let remaining = toast.timer.remaining_secs();
+        let hidden = (elapsed.min(remaining) / TOAST_SLIDE_SECONDS).min(1.);
+        // 滑入、滑出时向上偏移到画面外
+        let offset = (1. - hidden) * (win_size.height / 2. - toast.y + 60.);
+        transform.translation.y = toast.y + offset;
+    }
+}
+
+/// 菜单中进入成就列表
+fn enter_achievements_system(
+    kb: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if kb.just_released(KeyCode::A) {
+        next_state.set(GameState::Achievements);
+    }
+}
+
+/// 显示成就列表
+fn achievements_display_system(
+    mut commands: Commands,
+    config: Res<AchievementConfig>,
+    progress: Res<AchievementProgress>,
+    static_assets: Res<StaticAssets>,
+    win_size: Res<WinSize>,
+) {
+    let font = static_assets.kenney_future_font.clone();
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 32.,
+        color: Color::BLUE,
+    };
+    let name_style = TextStyle {
+        font: font.clone(),
+        font_size: 20.,
+        color: Color::RED,
+    };
+    let locked_style = TextStyle {
+        color: Color::GRAY,
+        ..name_style.clone()
+    };
+    let description_style = TextStyle {
+        font: font.clone(),
+        font_size: 14.,
+        color: Color::BLUE,
+    };
+
+    let unlocked = config
+        .achievements
+        .iter()
+        .filter(|achievement| progress.unlocked.contains(&achievement.id))
+        .count();
+    let mut sections = vec![TextSection::new(
+        format!(
+            "ACHIEVEMENTS {}/{}\r\n\r\n",
+            unlocked,
+            config.achievements.len()
+        ),
+        title_style,
+    )];
+    for achievement in config.achievements.iter() {
+        let (mark, style) = if progress.unlocked.contains(&achievement.id) {
+            ("[X] ", name_style.clone())
+        } else {
+            ("[ ] ", locked_style.clone())
+        };
+        sections.push(TextSection::new(
+            format!("{}{}\r\n", mark, achievement.name),
+            style,
+        ));
+        let description = match achievement.condition.progress(&progress) {
+            Some((current, target)) => {
+                format!("{} ({}/{})\r\n", achievement.description, current, target)
+            }
+            None => format!("{}\r\n", achievement.description),
+        };
+        sections.push(TextSection::new(description, description_style.clone()));
+    }
+    sections.push(TextSection::new(
+        "\r\nA BACK".to_owned(),
+        description_style.clone(),
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(sections).with_alignment(TextAlignment::Left),
+            text_anchor: Anchor::TopCenter,
+            transform: Transform {
+                translation: Vec3::new(0., win_size.height / 2. - 20., 6.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DisplayAchievements,
+    ));
+}
+
+/// 成就列表的键盘监听，A 或退格键返回菜单
+fn achievements_input_system(
+    kb: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if kb.just_released(KeyCode::A) || kb.just_released(KeyCode::Back) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+/// 退出成就列表
+fn exit_achievements_system(
+    mut commands: Commands,
+    query: Query<Entity, With<DisplayAchievements>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::{
+    Audio, DetectChanges, EventReader, EventWriter, Input, IntoSystemConfig, KeyCode,
+    PlaybackSettings, Plugin, Res, ResMut, Resource, State,
+};
+
+use crate::{
+    constants::PAUSED_DUCK_VOLUME,
+    events::{BirdCrashed, Flapped, PipePassed},
+    pause::PauseState,
+    resource::{Settings, StaticAssets},
+};
+
+/// 混音插件
+///
+/// * 所有声音都通过混音器播放，音量由设置中的主音量、通道音量、静音以及暂停时的压低共同决定
+pub struct MixerPlugin;
+
+impl Plugin for MixerPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<PlaySfx>()
+            .init_resource::<AudioMixer>()
+            .add_system(mute_toggle_system)
+            .add_system(mixer_update_system)
+            .add_system(gameplay_sfx_system.before(play_sfx_system))
+            .add_system(play_sfx_system);
+    }
+}
+
+/// 声音通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioChannel {
+    Sfx,
+    Music,
+}
+
+impl AudioChannel {
+    const ALL: [AudioChannel; 2] = [AudioChannel::Sfx, AudioChannel::Music];
+}
+
+/// 音效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sfx {
+    /// 飞行
+    Flap,
+    /// 得分
+    Point,
+    /// 被撞击
+    Hit,
+    /// 死亡
+    Die,
+}
+
+/// 播放音效事件
+pub struct PlaySfx(pub Sfx);
+
+/// 混音器资源，保存每个通道最终生效的音量
+#[derive(Resource, Default)]
+pub struct AudioMixer {
+    volumes: [f32; AudioChannel::ALL.len()],
+}
+
+impl AudioMixer {
+    /// 通道当前的音量
+    pub fn volume(&self, channel: AudioChannel) -> f32 {
+        self.volumes[channel as usize]
+    }
+}
+
+/// 根据设置和游戏状态计算每个通道的音量
+fn mixer_update_system(
+    settings: Res<Settings>,
+    pause_state: Res<State<PauseState>>,
+    mut mixer: ResMut<AudioMixer>,
+) {
+    if !settings.is_changed() && !pause_state.is_changed() {
+        return;
+    }
+    // 暂停和恢复倒计时期间压低音量
+    let duck = if pause_state.0 != PauseState::Running {
+        PAUSED_DUCK_VOLUME
+    } else {
+        1.
+    };
+    for channel in AudioChannel::ALL {
+        let channel_volume = match channel {
+            AudioChannel::Sfx => settings.sfx_volume,
+            AudioChannel::Music => settings.music_volume,
+        };
+        let volume = if settings.muted {
+            0.
+        } else {
+            settings.master_volume * channel_volume * duck
+        };
+        mixer.volumes[channel as usize] = volume;
+    }
+}
+
+/// 全局静音切换
+fn mute_toggle_system(kb: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if kb.just_pressed(KeyCode::N) {
+        settings.muted = !settings.muted;
+    }
+}
+
+/// 游戏事件对应的音效
+fn gameplay_sfx_system(
+    mut flapped_events: EventReader<Flapped>,
+    mut passed_events: EventReader<PipePassed>,
+    mut crashed_events: EventReader<BirdCrashed>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    for _ in flapped_events.iter() {
+        sfx_events.send(PlaySfx(Sfx::Flap));
+    }
+    for _ in passed_events.iter() {
+        sfx_events.send(PlaySfx(Sfx::Point));
+    }
+    // 播放撞击和死亡声音
+    for _ in crashed_events.iter() {
+        sfx_events.send_batch([PlaySfx(Sfx::Hit), PlaySfx(Sfx::Die)]);
+    }
+}
+
+/// 播放音效
+fn play_sfx_system(
+    mut events: EventReader<PlaySfx>,
+    mixer: Res<AudioMixer>,
+    static_assets: Res<StaticAssets>,
+    audio_player: Res<Audio>,
+) {
+    let volume = mixer.volume(AudioChannel::Sfx);
+    if volume <= 0. {
+        events.clear();
+        return;
+    }
+    for PlaySfx(sfx) in events.iter() {
+        let source = match sfx {
+            Sfx::Flap => static_assets.fly_audio.clone(),
+            Sfx::Point => static_assets.point_audio.clone(),
+            Sfx::Hit => static_assets.hit_audio.clone(),
+            Sfx::Die => static_assets.die_audio.clone(),
+        };
+        audio_player.play_with_settings(source, PlaybackSettings::ONCE.with_volume(volume));
+    }
+}
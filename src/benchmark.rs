@@ -0,0 +1,288 @@
+use std::{
+    fmt::{self, Write as _},
+    fs,
+    ops::Range,
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bot::BotPolicy,
+    events::CrashCause,
+    resource::{Difficulty, Settings},
+    simulation::Simulation,
+    state::GameState,
+};
+
+/// 批量评测
+///
+/// * 对种子范围内的每个种子，用同一个策略无界面地玩一局，没有渲染和帧率限制
+/// * 种子、策略和难度相同时结果完全相同，可以每天对比不同的策略
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    pub policy: BotPolicy,
+    pub difficulty: Difficulty,
+    pub seeds: Range<u64>,
+    /// 每局进入游戏后最多模拟的帧数
+    pub max_frames: u32,
+}
+
+/// 一局的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BenchmarkRun {
+    pub seed: u64,
+    pub score: u32,
+    /// 撞击的原因，超过最大帧数时为 `None`
+    pub cause: Option<CrashCause>,
+    /// 从进入游戏到撞击的帧数
+    pub frames: u32,
+    pub flaps: u32,
+}
+
+/// 撞击原因的统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeathCounts {
+    pub ceiling: u32,
+    pub ground: u32,
+    pub pipe: u32,
+    /// 超过最大帧数仍然存活
+    pub timeout: u32,
+}
+
+/// 所有局的汇总
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkSummary {
+    pub runs: u32,
+    pub mean_score: f64,
+    pub median_score: f64,
+    pub max_score: u32,
+    pub deaths: DeathCounts,
+    pub mean_frames: f64,
+    pub median_frames: f64,
+    pub max_frames: u32,
+}
+
+/// 评测报告
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub policy: BotPolicy,
+    pub difficulty: Difficulty,
+    pub seeds: Range<u64>,
+    pub summary: BenchmarkSummary,
+    pub runs: Vec<BenchmarkRun>,
+}
+
+impl Benchmark {
+    /// 依次模拟每个种子
+    pub fn run(&self) -> BenchmarkReport {
+        let runs: Vec<_> = self.seeds.clone().map(|seed| self.run_one(seed)).collect();
+        BenchmarkReport {
+            policy: self.policy,
+            difficulty: self.difficulty,
+            seeds: self.seeds.clone(),
+            summary: summarize(&runs),
+            runs,
+        }
+    }
+
+    /// 模拟一局，直到游戏结束或者超过最大帧数
+    pub fn run_one(&self, seed: u64) -> BenchmarkRun {
+        let mut sim = Simulation::new(seed);
+        sim.app().world.resource_mut::<Settings>().difficulty = self.difficulty;
+        sim.start_run();
+
+        let start = sim.frame();
+        let mut frames = None;
+        while sim.frame() - start < self.max_frames && sim.state() != GameState::GameOver {
+            self.policy.step(&mut sim);
+            if frames.is_none() && sim.state() == GameState::Dying {
+                frames = Some(sim.frame() - start);
+            }
+        }
+
+        let stats = sim.run_stats();
+        BenchmarkRun {
+            seed,
+            score: stats.score,
+            cause: stats.cause,
+            frames: frames.unwrap_or(sim.frame() - start),
+            flaps: stats.flaps,
+        }
+    }
+}
+
+/// 汇总得分、撞击原因和每局的长度
+pub fn summarize(runs: &[BenchmarkRun]) -> BenchmarkSummary {
+    let scores: Vec<_> = runs.iter().map(|run| run.score).collect();
+    let frames: Vec<_> = runs.iter().map(|run| run.frames).collect();
+    let mut deaths = DeathCounts::default();
+    for run in runs {
+        match run.cause {
+            Some(CrashCause::Ceiling) => deaths.ceiling += 1,
+            Some(CrashCause::Ground) => deaths.ground += 1,
+            Some(CrashCause::Pipe) => deaths.pipe += 1,
+            None => deaths.timeout += 1,
+        }
+    }
+    BenchmarkSummary {
+        runs: runs.len() as u32,
+        mean_score: mean(&scores),
+        median_score: median(&scores),
+        max_score: scores.iter().copied().max().unwrap_or(0),
+        deaths,
+        mean_frames: mean(&frames),
+        median_frames: median(&frames),
+        max_frames: frames.iter().copied().max().unwrap_or(0),
+    }
+}
+
+fn mean(values: &[u32]) -> f64 {
+    if values.is_empty() {
+        return 0.;
+    }
+    values.iter().map(|&value| value as f64).sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[u32]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let len = sorted.len();
+    match len {
+        0 => 0.,
+        _ if len % 2 == 1 => sorted[len / 2] as f64,
+        _ => (sorted[len / 2 - 1] as f64 + sorted[len / 2] as f64) / 2.,
+    }
+}
+
+impl BenchmarkReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("评测报告可以序列化")
+    }
+
+    /// 每局一行的 CSV
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("policy,difficulty,seed,score,cause,frames,flaps\n");
+        for run in &self.runs {
+            let cause = match run.cause {
+                Some(CrashCause::Ceiling) => "ceiling",
+                Some(CrashCause::Ground) => "ground",
+                Some(CrashCause::Pipe) => "pipe",
+                None => "timeout",
+            };
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                self.policy.name(),
+                self.difficulty.label().to_lowercase(),
+                run.seed,
+                run.score,
+                cause,
+                run.frames,
+                run.flaps
+            );
+        }
+        csv
+    }
+
+    /// 按扩展名导出为 CSV 或 JSON
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let content = if path.extension().is_some_and(|ext| ext == "csv") {
+            self.to_csv()
+        } else {
+            self.to_json()
+        };
+        fs::write(path, content).map_err(|err| format!("写入 {} 失败: {}", path.display(), err))
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = &self.summary;
+        let deaths = &summary.deaths;
+        writeln!(
+            f,
+            "策略 {}，难度 {}，种子 {}..{}，共 {} 局",
+            self.policy.name(),
+            self.difficulty.label().to_lowercase(),
+            self.seeds.start,
+            self.seeds.end,
+            summary.runs
+        )?;
+        writeln!(
+            f,
+            "得分: 平均 {:.2}，中位数 {:.1}，最高 {}",
+            summary.mean_score, summary.median_score, summary.max_score
+        )?;
+        writeln!(
+            f,
+            "撞击: 上边缘 {}，地面 {}，管道 {}，超时 {}",
+            deaths.ceiling, deaths.ground, deaths.pipe, deaths.timeout
+        )?;
+        writeln!(
+            f,
+            "帧数: 平均 {:.1}，中位数 {:.1}，最长 {}",
+            summary.mean_frames, summary.median_frames, summary.max_frames
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(score: u32, cause: Option<CrashCause>, frames: u32) -> BenchmarkRun {
+        BenchmarkRun {
+            seed: 0,
+            score,
+            cause,
+            frames,
+            flaps: 0,
+        }
+    }
+
+    #[test]
+    fn summarizes_scores_and_causes() {
+        let runs = [
+            run(1, Some(CrashCause::Pipe), 100),
+            run(4, Some(CrashCause::Pipe), 400),
+            run(2, Some(CrashCause::Ground), 200),
+            run(0, Some(CrashCause::Ceiling), 50),
+            run(9, None, 1000),
+        ];
+        let summary = summarize(&runs);
+        assert_eq!(summary.runs, 5);
+        assert_eq!(summary.mean_score, 3.2);
+        assert_eq!(summary.median_score, 2.);
+        assert_eq!(summary.max_score, 9);
+        assert_eq!(
+            summary.deaths,
+            DeathCounts {
+                ceiling: 1,
+                ground: 1,
+                pipe: 2,
+                timeout: 1,
+            }
+        );
+        assert_eq!(summary.median_frames, 200.);
+        assert_eq!(summary.max_frames, 1000);
+
+        assert_eq!(summarize(&runs[..4]).median_score, 1.5);
+        assert_eq!(summarize(&[]).mean_score, 0.);
+    }
+
+    #[test]
+    fn same_seeds_give_same_report() {
+        let benchmark = Benchmark {
+            policy: BotPolicy::FollowGap,
+            difficulty: Difficulty::Normal,
+            seeds: 0..3,
+            max_frames: 600,
+        };
+        let report = benchmark.run();
+        assert_eq!(report.runs.len(), 3);
+        assert_eq!(report.runs, benchmark.run().runs);
+        assert!(report.summary.max_score >= 1);
+        assert_eq!(report.to_csv().lines().count(), 4);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    cmp::Reverse,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use flappy_bird_bevy::{
+    constants::REPLAY_MAX_FRAMES,
+    leaderboard::{LeaderboardEntry, SignedRecord, SubmitResponse},
+};
+
+/// 命令行帮助
+const USAGE: &str = "\
+用法: leaderboard_server --key <密钥> [选项]
+
+本地测试用的排行榜服务器，成绩只保存在内存中。
+成绩必须带回放，重新模拟后得分或撞击帧数不一致时拒绝。
+
+选项:
+    --port <N>     监听的端口，0 表示随机选择，默认 7878
+    --key <密钥>   验证签名的密钥，和游戏的 FLAPPY_LEADERBOARD_KEY 一致，必须指定
+    --fail <N>     前 N 个请求返回 503，用于测试重试
+    -h, --help     显示帮助";
+
+/// 启动参数
+struct Options {
+    port: u16,
+    key: String,
+    fail: u32,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            port: 7878,
+            key: String::new(),
+            fail: 0,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} 缺少参数值", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--port" => {
+                    options.port = value()?
+                        .parse()
+                        .map_err(|_| "--port 的参数值无效".to_owned())?
+                }
+                "--key" => options.key = value()?,
+                "--fail" => {
+                    options.fail = value()?
+                        .parse()
+                        .map_err(|_| "--fail 的参数值无效".to_owned())?
+                }
+                _ => return Err(format!("未知参数 {}", arg)),
+            }
+        }
+        if options.key.is_empty() {
+            return Err("缺少 --key".to_owned());
+        }
+        Ok(Some(options))
+    }
+}
+
+/// 排行榜服务器
+struct Server {
+    key: String,
+    /// 剩余需要返回 503 的请求数
+    fail: u32,
+    records: Vec<SignedRecord>,
+}
+
+/// HTTP 请求
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+/// HTTP 响应
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, value: &impl serde::Serialize) -> Self {
+        Self {
+            status,
+            body: serde_json::to_string(value).expect("响应可以序列化"),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message }))
+    }
+}
+
+impl Server {
+    fn handle(&mut self, request: &Request) -> Response {
+        if self.fail > 0 {
+            self.fail -= 1;
+            return Response::error(503, "服务暂时不可用");
+        }
+        let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+        match (request.method.as_str(), path) {
+            ("GET", "/scores") => {
+                let limit = query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("limit="))
+                    .and_then(|limit| limit.parse().ok())
+                    .unwrap_or(10);
+                Response::json(200, &self.top(limit))
+            }
+            ("POST", "/scores") => {
+                let Ok(record) = serde_json::from_str::<SignedRecord>(&request.body) else {
+                    return Response::error(400, "记录格式错误");
+                };
+                if !record.verify(&self.key) {
+                    return Response::error(401, "签名错误");
+                }
+                // 重新模拟回放，没有回放或者结果不一致时拒绝
+                let verdict = record.record.verify_replay(REPLAY_MAX_FRAMES);
+                if let Some(reason) = verdict.reason {
+                    return Response::error(422, &reason);
+                }
+                let score = record.record.score;
+                println!("收到成绩 {} {}", record.record.player, score);
+                self.records.push(record);
+                let rank = self
+                    .records
+                    .iter()
+                    .filter(|record| record.record.score > score)
+                    .count() as u32
+                    + 1;
+                Response::json(201, &SubmitResponse { rank })
+            }
+            _ => Response::error(404, "没有这个接口"),
+        }
+    }
+
+    /// 按得分从高到低排列，得分相同时先提交的在前
+    fn top(&self, limit: usize) -> Vec<LeaderboardEntry> {
+        let mut records: Vec<_> = self.records.iter().map(|signed| &signed.record).collect();
+        records.sort_by_key(|record| Reverse(record.score));
+        records
+            .into_iter()
+            .take(limit)
+            .map(|record| LeaderboardEntry {
+                player: record.player.clone(),
+                score: record.score,
+                difficulty: record.difficulty,
+            })
+            .collect()
+    }
+}
+
+/// 读取一个请求，只支持带 `Content-Length` 的请求体
+fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|err| err.to_string())?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(format!("请求行格式错误: {:?}", line));
+    };
+    let (method, path) = (method.to_owned(), path.to_owned());
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|err| err.to_string())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| "Content-Length 无效")?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|err| err.to_string())?;
+    let body = String::from_utf8(body).map_err(|err| err.to_string())?;
+    Ok(Request { method, path, body })
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        422 => "Unprocessable Entity",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let listener = TcpListener::bind(("127.0.0.1", options.port)).unwrap_or_else(|err| {
+        eprintln!("监听端口 {} 失败: {}", options.port, err);
+        std::process::exit(1);
+    });
+    // 测试通过这一行获取实际监听的地址
+    println!(
+        "排行榜模拟服务器监听 {}",
+        listener.local_addr().expect("已经绑定地址")
+    );
+
+    let mut server = Server {
+        key: options.key,
+        fail: options.fail,
+        records: vec![],
+    };
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let response = match read_request(&stream) {
+            Ok(request) => server.handle(&request),
+            Err(err) => Response::error(400, &err),
+        };
+        if let Err(err) = write_response(&stream, &response) {
+            eprintln!("发送响应失败: {}", err);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use flappy_bird_bevy::{
+    constants::REPLAY_MAX_FRAMES,
+    leaderboard::SignedRecord,
+    replay::{self, Claim, Replay, Verdict},
+};
+
+/// 命令行帮助
+const USAGE: &str = "\
+用法: verify_replay <文件> [选项]
+
+重新模拟一局回放，检查声明的得分和撞击帧数，输出一行 JSON 结论。
+文件是 .json 时读取提交到排行榜的签名记录，检查签名并使用记录中的得分、撞击帧数和回放，
+否则读取 --record 录制的回放文件，需要用 --score 和 --death-frame 声明得分和撞击帧数。
+在最大帧数内没有撞击的回放无效。
+
+选项:
+    --score <N>          声明的得分，只用于回放文件
+    --death-frame <N>    声明的撞击帧数，只用于回放文件
+    --key <密钥>         验证记录签名的密钥，签名记录必须指定
+    --max-frames <N>     最多模拟的帧数，默认 36000
+    -h, --help           显示帮助
+
+退出码: 0 有效，1 无效，2 参数或文件错误";
+
+/// 启动参数
+struct Options {
+    file: PathBuf,
+    score: Option<u32>,
+    death_frame: Option<u32>,
+    key: Option<String>,
+    max_frames: u32,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut file = None;
+        let mut options = Options {
+            file: PathBuf::new(),
+            score: None,
+            death_frame: None,
+            key: None,
+            max_frames: REPLAY_MAX_FRAMES,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} 缺少参数值", arg));
+            let number = |value: String| value.parse().map_err(|_| format!("{} 的参数值无效", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--score" => options.score = Some(number(value()?)?),
+                "--death-frame" => options.death_frame = Some(number(value()?)?),
+                "--key" => options.key = Some(value()?),
+                "--max-frames" => options.max_frames = number(value()?)?,
+                _ if arg.starts_with('-') => return Err(format!("未知参数 {}", arg)),
+                _ if file.is_some() => return Err("只能指定一个文件".to_owned()),
+                _ => file = Some(PathBuf::from(arg)),
+            }
+        }
+        options.file = file.ok_or("缺少要验证的文件")?;
+        Ok(Some(options))
+    }
+}
+
+fn is_record(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// 读取文件并验证，文件或参数错误时返回 `Err`
+fn run(options: &Options) -> Result<Verdict, String> {
+    if !is_record(&options.file) {
+        let score = options.score.ok_or("验证回放文件需要 --score")?;
+        let death_frame = options
+            .death_frame
+            .ok_or("验证回放文件需要 --death-frame")?;
+        let replay = Replay::load(&options.file)?;
+        let claimed = Claim { score, death_frame };
+        return Ok(replay::verify(&replay, claimed, options.max_frames));
+    }
+
+    if options.score.is_some() || options.death_frame.is_some() {
+        return Err(
+            "签名记录使用记录中的得分和撞击帧数，不能指定 --score 或 --death-frame".to_owned(),
+        );
+    }
+    let key = options.key.as_deref().ok_or("验证签名记录需要 --key")?;
+    let content = fs::read_to_string(&options.file)
+        .map_err(|err| format!("读取 {} 失败: {}", options.file.display(), err))?;
+    let signed: SignedRecord = serde_json::from_str(&content)
+        .map_err(|err| format!("{} 格式错误: {}", options.file.display(), err))?;
+    if !signed.verify(key) {
+        return Ok(Verdict {
+            valid: false,
+            claimed: Claim {
+                score: signed.record.score,
+                death_frame: signed.record.death_frame,
+            },
+            actual: None,
+            reason: Some("签名错误".to_owned()),
+        });
+    }
+    Ok(signed.record.verify_replay(options.max_frames))
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(verdict) => {
+            println!(
+                "{}",
+                serde_json::to_string(&verdict).expect("结论可以序列化")
+            );
+            if verdict.valid {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{BIRD_IMG_SIZE, PIPE_IMG_SIZE},
+    simulation::Simulation,
+};
+
+/// 飞行前允许低于目标高度的距离，避免每帧都飞行
+const BOT_TOLERANCE: f32 = 10.;
+
+/// 自动玩游戏的策略，用于无界面的批量评测
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BotPolicy {
+    /// 从不飞行
+    Idle,
+    /// 保持在画面中间的高度，不看管道
+    Hover,
+    /// 跟随前方管道的可通过区域中心
+    FollowGap,
+}
+
+impl BotPolicy {
+    pub const ALL: [BotPolicy; 3] = [BotPolicy::Idle, BotPolicy::Hover, BotPolicy::FollowGap];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotPolicy::Idle => "idle",
+            BotPolicy::Hover => "hover",
+            BotPolicy::FollowGap => "follow-gap",
+        }
+    }
+
+    /// 按名称查找策略
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|policy| policy.name() == name)
+    }
+
+    /// 这一帧是否飞行
+    pub fn wants_flap(&self, sim: &mut Simulation) -> bool {
+        let target = match self {
+            BotPolicy::Idle => return false,
+            BotPolicy::Hover => Some(0.),
+            BotPolicy::FollowGap => next_gap_center(sim),
+        };
+        match (sim.bird(), target) {
+            (Some(bird), Some(target)) => bird.y < target - BOT_TOLERANCE,
+            _ => false,
+        }
+    }
+
+    /// 按策略推进一帧
+    pub fn step(&self, sim: &mut Simulation) {
+        if self.wants_flap(sim) {
+            sim.flap();
+        } else {
+            sim.step();
+        }
+    }
+}
+
+/// 小鸟前方还没有通过的那组管道的可通过区域中心
+pub fn next_gap_center(sim: &mut Simulation) -> Option<f32> {
+    let bird = sim.bird()?;
+    let obstacles = sim.obstacles();
+    let ahead: Vec<_> = obstacles
+        .iter()
+        .filter(|pipe| pipe.x + PIPE_IMG_SIZE.0 / 2. > bird.x - BIRD_IMG_SIZE.0 / 2.)
+        .collect();
+    let x = ahead.first()?.x;
+    let pair: Vec<_> = ahead.iter().filter(|pipe| pipe.x == x).collect();
+    let lower = pair.iter().map(|pipe| pipe.y).fold(f32::MAX, f32::min);
+    let upper = pair.iter().map(|pipe| pipe.y).fold(f32::MIN, f32::max);
+    Some((lower + PIPE_IMG_SIZE.1 / 2. + upper - PIPE_IMG_SIZE.1 / 2.) / 2.)
+}
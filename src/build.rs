@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-const COPY_DIR: &'static str = "assets";
+const COPY_DIR: &str = "assets";
 
 /// A helper function for recursively copying a directory.
 fn copy_dir<P, Q>(from: P, to: Q)
@@ -45,4 +45,4 @@ fn main() {
 
     // Copy the directory
     copy_dir(COPY_DIR, &out);
-}
\ No newline at end of file
+}
@@ -0,0 +1,455 @@
+use std::{path::PathBuf, str::FromStr};
+
+use bevy::prelude::{App, Plugin, Resource};
+use serde::Serialize;
+
+use crate::{
+    benchmark::Benchmark,
+    bot::BotPolicy,
+    constants::{REPLAY_MAX_FRAMES, START_FLAPS, WINDOW_SCALES},
+    pause::PauseDisabled,
+    replay::{play, Replay, ReplayPlayback, ReplayPlugin, ReplayRecorder, ReplayResult},
+    resource::{Difficulty, GameRng, Settings},
+    run_log::RunLog,
+    simulation::FixedStepPlugin,
+};
+
+/// 命令行帮助
+pub const USAGE: &str = "\
+用法: flappy_bird_bevy [选项]
+
+选项:
+    --seed <N>                 管道随机数的种子，之后每一局的种子由它生成
+    --difficulty <难度>        easy、normal 或 hard，只对本次启动生效
+    --scale <倍数>             窗口缩放，1、1.5 或 2，只对本次启动生效
+    --mute                     静音启动
+    --start-in-game            跳过菜单和准备，直接开始游戏
+    --practice                 启用练习工具（无敌、慢动作、单步等），练习的分数不计入记录
+    --replay <文件>            播放回放文件
+    --record <文件>            录制第一局的输入，结束时保存为回放文件
+    --run-log <文件>           每局结束时追加统计的文件，扩展名为 .csv 时写入 CSV，否则写入 JSON Lines
+    --headless                 不打开窗口，模拟回放或者批量评测
+    --runs <N>                 批量评测的局数，种子依次加一
+    --bot <策略>               批量评测使用的策略，idle、hover 或 follow-gap
+    --export <文件>            导出每一局的评测结果，扩展名为 .csv 时导出 CSV，否则导出 JSON
+    --monitor <N>              在第 N 个显示器上居中
+    --window-position <X>,<Y>  窗口左上角的坐标
+    -h, --help                 显示帮助";
+
+/// 启动参数
+///
+/// * 难度、缩放和静音覆盖本地的设置，但不会写回设置文件
+/// * 显示器和窗口位置覆盖上次保存的窗口位置
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LaunchOptions {
+    pub seed: Option<u64>,
+    pub difficulty: Option<Difficulty>,
+    pub scale: Option<f32>,
+    pub mute: bool,
+    pub start_in_game: bool,
+    /// 启用练习工具
+    pub practice: bool,
+    pub replay: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub headless: bool,
+    /// 每局统计的文件，不指定时不记录
+    pub run_log: Option<PathBuf>,
+    /// 批量评测的局数
+    pub runs: u32,
+    /// 批量评测的策略
+    pub bot: BotPolicy,
+    /// 批量评测结果的导出路径
+    pub export: Option<PathBuf>,
+    /// 在第几个显示器上居中
+    pub monitor: Option<usize>,
+    /// 窗口左上角的坐标
+    pub window_position: Option<(i32, i32)>,
+    pub help: bool,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            difficulty: None,
+            scale: None,
+            mute: false,
+            start_in_game: false,
+            practice: false,
+            replay: None,
+            record: None,
+            run_log: None,
+            headless: false,
+            runs: 1,
+            bot: BotPolicy::Idle,
+            export: None,
+            monitor: None,
+            window_position: None,
+            help: false,
+        }
+    }
+}
+
+impl LaunchOptions {
+    /// 解析命令行参数（不包括程序名）
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut runs = None;
+        let mut bot = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => options.help = true,
+                "--seed" => options.seed = Some(value(&mut args, &arg)?),
+                "--difficulty" => {
+                    let name: String = value(&mut args, &arg)?;
+                    options.difficulty = Some(parse_difficulty(&name)?);
+                }
+                "--scale" => {
+                    let scale: f32 = value(&mut args, &arg)?;
+                    if !WINDOW_SCALES.contains(&scale) {
+                        return Err(format!("--scale 只能是 {:?} 之一", WINDOW_SCALES));
+                    }
+                    options.scale = Some(scale);
+                }
+                "--mute" => options.mute = true,
+                "--start-in-game" => options.start_in_game = true,
+                "--practice" => options.practice = true,
+                "--replay" => options.replay = Some(value(&mut args, &arg)?),
+                "--record" => options.record = Some(value(&mut args, &arg)?),
+                "--run-log" => options.run_log = Some(value(&mut args, &arg)?),
+                "--headless" => options.headless = true,
+                "--runs" => runs = Some(value(&mut args, &arg)?),
+                "--bot" => {
+                    let name: String = value(&mut args, &arg)?;
+                    bot = Some(BotPolicy::from_name(&name).ok_or_else(|| {
+                        format!("未知策略 {}，可选 idle、hover、follow-gap", name)
+                    })?);
+                }
+                "--export" => options.export = Some(value(&mut args, &arg)?),
+                "--monitor" => options.monitor = Some(value(&mut args, &arg)?),
+                "--window-position" => {
+                    let position: String = value(&mut args, &arg)?;
+                    options.window_position = Some(parse_position(&position).ok_or_else(|| {
+                        format!("--window-position 的参数值 {} 无效，格式为 X,Y", position)
+                    })?);
+                }
+                _ => return Err(format!("未知参数 {}", arg)),
+            }
+        }
+
+        if options.replay.is_some() {
+            if options.record.is_some() {
+                return Err("--replay 和 --record 不能同时使用".to_owned());
+            }
+            if options.seed.is_some() || options.difficulty.is_some() {
+                return Err("回放文件中已经包含种子和难度".to_owned());
+            }
+            if runs.is_some() || bot.is_some() || options.export.is_some() {
+                return Err("--replay 不能和 --runs、--bot、--export 同时使用".to_owned());
+            }
+        }
+        if !options.headless && (bot.is_some() || options.export.is_some()) {
+            return Err("--bot 和 --export 只能和 --headless 一起使用".to_owned());
+        }
+        if options.headless && options.run_log.is_some() {
+            return Err("--run-log 只能在窗口模式下使用，无界面模式使用 --export".to_owned());
+        }
+        if let Some(bot) = bot {
+            options.bot = bot;
+        }
+        if options.headless && (options.mute || options.scale.is_some()) {
+            return Err("--mute 和 --scale 只能在窗口模式下使用".to_owned());
+        }
+        if options.headless && (options.start_in_game || options.practice) {
+            return Err("--start-in-game 和 --practice 只能在窗口模式下使用".to_owned());
+        }
+        if options.headless && options.record.is_some() {
+            return Err("--record 只能在窗口模式下使用".to_owned());
+        }
+        if options.monitor.is_some() && options.window_position.is_some() {
+            return Err("--monitor 和 --window-position 不能同时使用".to_owned());
+        }
+        if options.headless && (options.monitor.is_some() || options.window_position.is_some()) {
+            return Err("--monitor 和 --window-position 只能在窗口模式下使用".to_owned());
+        }
+        if let Some(runs) = runs {
+            if !options.headless {
+                return Err("--runs 只能和 --headless 一起使用".to_owned());
+            }
+            if runs == 0 {
+                return Err("--runs 必须大于 0".to_owned());
+            }
+            options.runs = runs;
+        }
+        Ok(options)
+    }
+
+    /// 用启动参数覆盖设置
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(difficulty) = self.difficulty {
+            settings.difficulty = difficulty;
+        }
+        if let Some(scale) = self.scale {
+            settings.window_scale = scale;
+        }
+        if self.mute {
+            settings.muted = true;
+        }
+    }
+
+    /// 去掉启动参数的覆盖，得到要写回设置文件的设置
+    ///
+    /// * 仍然等于覆盖值的设置项恢复为设置文件中的值，在游戏中修改过的设置项照常保存
+    pub fn remove_overrides(&self, settings: &Settings, stored: &Settings) -> Settings {
+        let mut saved = settings.clone();
+        if self.difficulty == Some(settings.difficulty) {
+            saved.difficulty = stored.difficulty;
+        }
+        if self.scale == Some(settings.window_scale) {
+            saved.window_scale = stored.window_scale;
+        }
+        if self.mute && settings.muted {
+            saved.muted = stored.muted;
+        }
+        saved
+    }
+}
+
+/// 解析 `X,Y` 格式的坐标
+fn parse_position(value: &str) -> Option<(i32, i32)> {
+    let (x, y) = value.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// 读取参数的值
+fn value<T: FromStr>(args: &mut impl Iterator<Item = String>, name: &str) -> Result<T, String> {
+    let value = args.next().ok_or_else(|| format!("{} 缺少参数值", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("{} 的参数值 {} 无效", name, value))
+}
+
+/// 按名称解析难度，不区分大小写
+fn parse_difficulty(name: &str) -> Result<Difficulty, String> {
+    [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard]
+        .into_iter()
+        .find(|difficulty| difficulty.label().eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("未知难度 {}，可选 easy、normal、hard", name))
+}
+
+/// 启动插件
+///
+/// * 根据启动参数固定种子，开始回放或录制，或者直接进入游戏
+pub struct LaunchPlugin {
+    options: LaunchOptions,
+    replay: Option<Replay>,
+}
+
+impl LaunchPlugin {
+    /// 读取要播放的回放文件
+    pub fn new(options: LaunchOptions) -> Result<Self, String> {
+        let replay = options.replay.as_deref().map(Replay::load).transpose()?;
+        Ok(Self { options, replay })
+    }
+}
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        let mut options = self.options.clone();
+        if let Some(replay) = &self.replay {
+            options.difficulty = Some(replay.difficulty);
+        }
+        let seed = self
+            .replay
+            .as_ref()
+            .map(|replay| replay.seed)
+            .or(options.seed);
+        if let Some(seed) = seed {
+            app.insert_resource(GameRng::from_seed(seed));
+        }
+
+        if self.replay.is_some() {
+            app.add_plugin(FixedStepPlugin)
+                .insert_resource(PauseDisabled);
+        }
+        if let Some(replay) = &self.replay {
+            app.insert_resource(ReplayPlayback::new(replay.flaps.clone()));
+        } else if options.start_in_game {
+            app.insert_resource(ReplayPlayback::new(START_FLAPS));
+        }
+        if let Some(path) = &options.record {
+            app.insert_resource(ReplayRecorder::new(path.clone()));
+        }
+        if let Some(path) = &options.run_log {
+            app.insert_resource(RunLog {
+                path: Some(path.clone()),
+            });
+        }
+        app.insert_resource(options).add_plugin(ReplayPlugin);
+    }
+}
+
+/// 无界面模式下回放的结果
+#[derive(Serialize)]
+struct HeadlessRun<'a> {
+    seed: u64,
+    difficulty: Difficulty,
+    #[serde(flatten)]
+    result: &'a ReplayResult,
+}
+
+/// 无界面模式
+///
+/// * 有回放文件时模拟回放，输出一行 JSON 结果
+/// * 否则用 `--bot` 指定的策略批量评测 `--runs` 局，输出汇总，`--export` 导出每一局的结果
+pub fn run_headless(options: &LaunchOptions) -> Result<(), String> {
+    if let Some(path) = &options.replay {
+        let replay = Replay::load(path)?;
+        let result = play(&replay, REPLAY_MAX_FRAMES);
+        let run = HeadlessRun {
+            seed: replay.seed,
+            difficulty: replay.difficulty,
+            result: &result,
+        };
+        let line = serde_json::to_string(&run).map_err(|err| err.to_string())?;
+        println!("{}", line);
+        return Ok(());
+    }
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let benchmark = Benchmark {
+        policy: options.bot,
+        difficulty: options.difficulty.unwrap_or_default(),
+        seeds: seed..seed.saturating_add(options.runs as u64),
+        max_frames: REPLAY_MAX_FRAMES,
+    };
+    let report = benchmark.run();
+    if let Some(path) = &options.export {
+        report.export(path)?;
+    }
+    print!("{}", report);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_args_is_default() {
+        assert_eq!(parse(&[]), Ok(LaunchOptions::default()));
+    }
+
+    #[test]
+    fn parses_all_options() {
+        let options = parse(&[
+            "--seed",
+            "42",
+            "--difficulty",
+            "Hard",
+            "--scale",
+            "1.5",
+            "--mute",
+            "--start-in-game",
+            "--practice",
+            "--record",
+            "run.ron",
+            "--run-log",
+            "runs.jsonl",
+            "--monitor",
+            "1",
+        ])
+        .unwrap();
+        assert_eq!(options.monitor, Some(1));
+        assert_eq!(options.seed, Some(42));
+        assert_eq!(options.difficulty, Some(Difficulty::Hard));
+        assert_eq!(options.scale, Some(1.5));
+        assert!(options.mute && options.start_in_game && options.practice);
+        assert_eq!(options.record, Some(PathBuf::from("run.ron")));
+        assert_eq!(options.run_log, Some(PathBuf::from("runs.jsonl")));
+
+        let options = parse(&[
+            "--headless",
+            "--runs",
+            "10",
+            "--bot",
+            "follow-gap",
+            "--export",
+            "runs.csv",
+        ])
+        .unwrap();
+        assert!(options.headless);
+        assert_eq!(options.runs, 10);
+        assert_eq!(options.bot, BotPolicy::FollowGap);
+        assert_eq!(options.export, Some(PathBuf::from("runs.csv")));
+
+        let options = parse(&["--window-position", "1920, -8"]).unwrap();
+        assert_eq!(options.window_position, Some((1920, -8)));
+    }
+
+    #[test]
+    fn rejects_invalid_args() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--difficulty", "insane"]).is_err());
+        assert!(parse(&["--scale", "3"]).is_err());
+        assert!(parse(&["--fly"]).is_err());
+        assert!(parse(&["--runs", "5"]).is_err());
+        assert!(parse(&["--headless", "--runs", "0"]).is_err());
+        assert!(parse(&["--headless", "--record", "run.ron"]).is_err());
+        assert!(parse(&["--replay", "run.ron", "--seed", "1"]).is_err());
+        assert!(parse(&["--bot", "idle"]).is_err());
+        assert!(parse(&["--headless", "--bot", "genius"]).is_err());
+        assert!(parse(&["--monitor", "-1"]).is_err());
+        assert!(parse(&["--window-position", "1920"]).is_err());
+        assert!(parse(&["--monitor", "1", "--window-position", "0,0"]).is_err());
+        assert!(parse(&["--headless", "--monitor", "1"]).is_err());
+    }
+
+    #[test]
+    fn rejects_window_options_in_headless_mode() {
+        for option in [
+            &["--mute"][..],
+            &["--scale", "2"],
+            &["--start-in-game"],
+            &["--practice"],
+        ] {
+            let args: Vec<_> = ["--headless"].iter().chain(option).copied().collect();
+            let err = parse(&args).unwrap_err();
+            assert!(err.contains(option[0]), "{}", err);
+        }
+    }
+
+    #[test]
+    fn overrides_settings() {
+        let options = parse(&["--difficulty", "easy", "--scale", "2", "--mute"]).unwrap();
+        let mut settings = Settings::default();
+        options.apply(&mut settings);
+        assert_eq!(settings.difficulty, Difficulty::Easy);
+        assert_eq!(settings.window_scale, 2.);
+        assert!(settings.muted);
+    }
+
+    #[test]
+    fn overrides_are_not_saved() {
+        let options = parse(&["--difficulty", "easy", "--scale", "2", "--mute"]).unwrap();
+        let stored = Settings::default();
+        let mut settings = stored.clone();
+        options.apply(&mut settings);
+        assert_eq!(options.remove_overrides(&settings, &stored), stored);
+
+        // 在游戏中修改过的设置项照常保存
+        settings.difficulty = Difficulty::Hard;
+        settings.show_fps = true;
+        let saved = options.remove_overrides(&settings, &stored);
+        assert_eq!(saved.difficulty, Difficulty::Hard);
+        assert!(saved.show_fps);
+        assert_eq!(saved.window_scale, stored.window_scale);
+        assert!(!saved.muted);
+    }
+}
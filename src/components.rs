@@ -1,5 +1,5 @@
 use bevy::{
-    prelude::Component,
+    prelude::{Component, Vec2},
     time::{Timer, TimerMode},
 };
 
@@ -22,20 +22,12 @@ impl Default for PlayerAnimationTimer {
 pub struct Obstacle;
 
 /// 移动组件
-#[derive(Component)]
+#[derive(Component, Default)]
 pub struct Movable {
     /// 移动时是否需要旋转
     pub need_rotation: bool,
 }
 
-impl Default for Movable {
-    fn default() -> Self {
-        Self {
-            need_rotation: false,
-        }
-    }
-}
-
 /// 速度组件
 #[derive(Component)]
 pub struct Velocity {
@@ -61,6 +53,110 @@ pub struct DisplayMenu;
 #[derive(Component)]
 pub struct Ground(pub f32);
 
+/// 视差背景图块组件
+#[derive(Component)]
+pub struct ParallaxTile {
+    /// 所属图层的名称
+    pub layer: String,
+    /// 相对管道移动速度的比例
+    pub speed: f32,
+    /// 图块宽度
+    pub width: f32,
+    /// 整个图层平铺后的宽度
+    pub span: f32,
+}
+
 /// 游戏结束组件
 #[derive(Component)]
 pub struct DisplayGameOver;
+
+/// 设置菜单显示组件
+#[derive(Component, Default)]
+pub struct DisplaySettings {
+    /// 当前选中的设置项
+    pub selected: usize,
+}
+
+/// 帧率显示组件
+#[derive(Component)]
+pub struct DisplayFps;
+
+/// 游戏区域外的黑边组件
+#[derive(Component)]
+pub struct Letterbox;
+
+/// 天气粒子组件
+#[derive(Component)]
+pub struct WeatherParticle;
+
+/// 粒子组件
+#[derive(Component)]
+pub struct Particle {
+    /// 速度（像素/秒）
+    pub velocity: Vec2,
+    /// y 轴加速度（像素/秒²）
+    pub gravity: f32,
+    /// 存活时间，结束后销毁
+    pub lifetime: Timer,
+}
+
+/// 死亡时的白色闪光组件
+#[derive(Component)]
+pub struct DeathFlash(pub Timer);
+
+/// 从上方滑入的面板组件
+#[derive(Component)]
+pub struct SlideIn {
+    /// 起始 y 坐标
+    pub from: f32,
+    /// 目标 y 坐标
+    pub to: f32,
+    pub timer: Timer,
+}
+
+/// 准备状态提示组件
+#[derive(Component)]
+pub struct DisplayReady;
+
+/// 暂停界面组件
+#[derive(Component)]
+pub struct DisplayPause;
+
+/// 分数滚动显示组件，从 0 增加到目标分数
+#[derive(Component)]
+pub struct CountUp {
+    pub target: u8,
+    pub timer: Timer,
+}
+
+/// 分数滚动结束后才显示的组件（奖牌、新纪录标记）
+#[derive(Component)]
+pub struct RevealAfterCountUp;
+
+/// 成就列表显示组件
+#[derive(Component)]
+pub struct DisplayAchievements;
+
+/// 成就解锁提示组件
+#[derive(Component)]
+pub struct Toast {
+    /// 完全显示时的 y 坐标
+    pub y: f32,
+    pub timer: Timer,
+}
+
+/// 调试图形组件，每帧重新生成
+#[derive(Component)]
+pub struct DebugShape;
+
+/// 调试信息显示组件
+#[derive(Component)]
+pub struct DisplayDebug;
+
+/// 练习工具状态显示组件
+#[derive(Component)]
+pub struct DisplayPractice;
+
+/// 菜单上的排行榜显示组件
+#[derive(Component)]
+pub struct DisplayLeaderboard;
@@ -0,0 +1,25 @@
+use serde::de::DeserializeOwned;
+
+/// 资源目录名称
+const ASSETS_DIR: &str = "assets";
+
+/// 读取 assets 目录下的 ron 配置文件
+///
+/// * 桌面平台优先读取磁盘上的文件，修改配置不需要重新编译
+/// * 读取失败或者在 wasm 平台上使用编译时嵌入的内容
+pub fn load_config<T: DeserializeOwned>(path: &str, embedded: &str) -> T {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let file = bevy::asset::FileAssetIo::get_base_path()
+            .join(ASSETS_DIR)
+            .join(path);
+        if let Ok(content) = std::fs::read_to_string(&file) {
+            match ron::from_str(&content) {
+                Ok(config) => return config,
+                Err(err) => bevy::log::warn!("读取 {} 失败，使用内置配置: {}", file.display(), err),
+            }
+        }
+    }
+    ron::from_str(embedded)
+        .unwrap_or_else(|err| panic!("内置配置 {}/{} 格式错误: {}", ASSETS_DIR, path, err))
+}
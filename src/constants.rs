@@ -5,12 +5,14 @@ pub const BIRD_IMG_SIZE: (f32, f32) = (34., 24.);
 /// 小鸟动画帧数
 pub const BIRD_ANIMATION_LEN: usize = 3;
 
+/// 游戏区域的逻辑宽度，同时也是窗口的默认宽度
 pub const WINDOW_WIDTH: f32 = 576.;
+/// 游戏区域的逻辑高度，同时也是窗口的默认高度
 pub const WINDOW_HEIGHT: f32 = 624.;
+/// 黑边的大小，需要足够遮住游戏区域以外的部分
+pub const LETTERBOX_SIZE: f32 = 10000.;
 
-/// 背景图片路径
-pub const BACKGROUND_IMG_PATH: &str = "images/background.png";
-/// 背景图片大小
+/// 背景区域大小，地面紧接在它的下方
 pub const BACKGROUND_IMG_SIZE: (f32, f32) = (288., 512.);
 /// 地面图片路径
 pub const GROUND_IMG_PATH: &str = "images/ground.png";
@@ -30,11 +32,19 @@ pub const POINT_AUDIO_PATH: &str = "audios/point.ogg";
 pub const DIE_AUDIO_PATH: &str = "audios/die.ogg";
 /// 被撞击声音
 pub const HIT_AUDIO_PATH: &str = "audios/hit.ogg";
+/// 视差背景图层配置路径
+pub const LAYERS_CONFIG_PATH: &str = "layers.ron";
+/// 主题配置路径
+pub const THEMES_CONFIG_PATH: &str = "themes.ron";
+/// 背景音乐配置路径
+pub const MUSIC_CONFIG_PATH: &str = "music.ron";
 /// kenney future 字体路径
 pub const KENNEY_FUTURE_FONT_PATH: &str = "fonts/KenneyFuture.ttf";
 
 /// x 轴前进速度
 pub const SPAWN_OBSTACLE_TICK: f32 = 4.;
+/// 检查是否需要生成管道的间隔（秒）
+pub const SPAWN_OBSTACLE_CHECK_SECONDS: f32 = 0.2;
 /// x 轴前进速度
 pub const PLAYER_X_MAX_VELOCITY: f32 = 48.;
 /// y 轴最大上升速度
@@ -52,3 +62,82 @@ pub const TIME_STEP: f32 = 1. / 60.;
 pub const GAP_MAX: f32 = 300.;
 /// 最小通过空间
 pub const GAP_MIN: f32 = 50.;
+/// 可通过区域与画面上边缘、地面之间的最小距离
+pub const GAP_EDGE_MARGIN: f32 = 20.;
+
+/// 设置文件名称
+pub const SETTINGS_FILE_NAME: &str = "settings.ron";
+/// 窗口位置文件名称
+pub const WINDOW_FILE_NAME: &str = "window.ron";
+/// 音量调整步长
+pub const VOLUME_STEP: f32 = 0.1;
+/// 可选的窗口缩放倍数
+pub const WINDOW_SCALES: [f32; 3] = [1., 1.5, 2.];
+/// 暂停时音量压低的比例
+pub const PAUSED_DUCK_VOLUME: f32 = 0.3;
+/// 风力把小鸟拉回原位的弹性系数
+pub const WIND_SPRING: f32 = 1.5;
+/// 小鸟水平速度的阻尼系数
+pub const WIND_DAMPING: f32 = 1.2;
+/// 雨滴下落速度
+pub const RAIN_FALL_VELOCITY: f32 = 420.;
+/// 雪花下落速度
+pub const SNOW_FALL_VELOCITY: f32 = 70.;
+/// 死亡时白色闪光的时长（秒）
+pub const DEATH_FLASH_SECONDS: f32 = 0.25;
+/// 死亡时画面震动的时长（秒）
+pub const DEATH_SHAKE_SECONDS: f32 = 0.4;
+/// 画面震动的最大偏移
+pub const DEATH_SHAKE_INTENSITY: f32 = 8.;
+/// 死亡后下落的加速度
+pub const DEATH_GRAVITY_VELOCITY: f32 = 1200.;
+/// 死亡后最大下落速度
+pub const DEATH_MAX_FALL_VELOCITY: f32 = 600.;
+/// 死亡后机头朝下旋转的角速度（弧度/秒）
+pub const DEATH_ROTATION_SPEED: f32 = 8.;
+/// 游戏结束面板滑入的时长（秒）
+pub const GAME_OVER_SLIDE_SECONDS: f32 = 0.5;
+/// 从暂停恢复时的倒计时（秒）
+pub const RESUME_COUNTDOWN_SECONDS: f32 = 3.;
+/// 准备状态下小鸟上下浮动的幅度
+pub const BIRD_BOB_AMPLITUDE: f32 = 6.;
+/// 准备状态下小鸟上下浮动的角速度（弧度/秒）
+pub const BIRD_BOB_SPEED: f32 = 6.;
+/// 记录文件名称
+pub const RECORDS_FILE_NAME: &str = "records.ron";
+/// 铜、银、金、白金奖牌需要的分数
+pub const MEDAL_SCORES: [u8; 4] = [10, 20, 30, 40];
+/// 结算面板分数滚动的时长（秒）
+pub const SCORE_COUNT_UP_SECONDS: f32 = 0.8;
+/// 成就配置路径
+pub const ACHIEVEMENTS_CONFIG_PATH: &str = "achievements.ron";
+/// 成就进度文件名称
+pub const ACHIEVEMENTS_FILE_NAME: &str = "achievements.ron";
+/// 成就提示显示的时长（秒）
+pub const TOAST_SECONDS: f32 = 2.5;
+/// 成就提示滑入滑出的时长（秒）
+pub const TOAST_SLIDE_SECONDS: f32 = 0.3;
+/// 通过管道时与管道的距离小于该值算作擦着管道通过
+pub const NEAR_MISS_DISTANCE: f32 = 8.;
+/// 两次飞行的间隔不超过该值（秒）算作连续飞行
+pub const FLAP_STREAK_SECONDS: f32 = 1.;
+/// 回放最多模拟的帧数（10 分钟）
+pub const REPLAY_MAX_FRAMES: u32 = 60 * 60 * 10;
+/// 调试图形的线宽
+pub const DEBUG_LINE_WIDTH: f32 = 1.;
+/// 调试时速度向量的缩放（每秒的速度对应的长度）
+pub const DEBUG_VELOCITY_SCALE: f32 = 0.25;
+/// 练习时可选的时间缩放
+pub const PRACTICE_TIME_SCALES: [f32; 4] = [0.1, 0.25, 0.5, 1.];
+/// 练习时按 1-4 生成的可通过区域大小
+pub const PRACTICE_GAPS: [f32; 4] = [GAP_MIN, 100., 150., 200.];
+/// 练习时每次跳转增加的得分
+pub const PRACTICE_SCORE_STEP: u8 = 10;
+/// 从菜单进入游戏时按下空格的帧数（菜单、准备各一次）
+pub const START_FLAPS: [u32; 2] = [1, 3];
+/// 排行榜配置路径
+pub const LEADERBOARD_CONFIG_PATH: &str = "leaderboard.ron";
+/// 等待提交到排行榜的记录的文件名称
+pub const LEADERBOARD_QUEUE_FILE_NAME: &str = "leaderboard_queue.ron";
+/// 排行榜签名密钥的环境变量
+pub const LEADERBOARD_KEY_ENV: &str = "FLAPPY_LEADERBOARD_KEY";
@@ -0,0 +1,171 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    core_pipeline::core_2d::Camera2d,
+    prelude::{
+        Color, Commands, Entity, EulerRot, IntoSystemAppConfig, IntoSystemConfigs, NextState,
+        OnEnter, OnUpdate, Plugin, Quat, Query, Res, ResMut, Resource, Sprite, SpriteBundle,
+        Transform, Vec2, Vec3, With,
+    },
+    time::{Time, Timer, TimerMode},
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    components::{DeathFlash, Player, Velocity},
+    constants::{
+        BIRD_IMG_SIZE, DEATH_FLASH_SECONDS, DEATH_GRAVITY_VELOCITY, DEATH_MAX_FALL_VELOCITY,
+        DEATH_ROTATION_SPEED, DEATH_SHAKE_INTENSITY, DEATH_SHAKE_SECONDS, GROUND_IMG_SIZE,
+    },
+    pause::GameplaySet,
+    resource::{GameClock, WinSize},
+    state::GameState,
+};
+
+/// 死亡动画插件
+///
+/// * 撞击后先进入 `Dying` 状态：白色闪光、画面震动，小鸟机头朝下落到地面
+/// * 小鸟落地并且震动结束后才进入 `GameOver`，期间不响应按键
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(death_enter_system.in_schedule(OnEnter(GameState::Dying)))
+            .add_systems(
+                (death_fall_system, death_finish_system)
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::Dying)),
+            )
+            .add_systems((death_flash_system, camera_shake_system));
+    }
+}
+
+/// 画面震动资源，震动结束后移除
+#[derive(Resource)]
+pub struct CameraShake(Timer);
+
+/// 开始死亡动画
+fn death_enter_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    mut query: Query<&mut Velocity, With<Player>>,
+) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::WHITE,
+                custom_size: Some(Vec2::new(win_size.width, win_size.height)),
+                ..Default::default()
+            },
+            transform: Transform {
+                // 在所有游戏内容之上，黑边之下
+                translation: Vec3::new(0., 0., 40.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DeathFlash(Timer::from_seconds(DEATH_FLASH_SECONDS, TimerMode::Once)),
+    ));
+    commands.insert_resource(CameraShake(Timer::from_seconds(
+        DEATH_SHAKE_SECONDS,
+        TimerMode::Once,
+    )));
+
+    // 撞击后不再有水平速度，也不再继续上升
+    for mut velocity in query.iter_mut() {
+        velocity.x = 0.;
+        velocity.y = velocity.y.min(0.);
+    }
+}
+
+/// 小鸟的落地高度
+fn landing_y(win_size: &WinSize) -> f32 {
+    // 机头朝下时，小鸟的宽度变成了高度
+    -(win_size.height / 2. - GROUND_IMG_SIZE.1) + BIRD_IMG_SIZE.0 / 2.
+}
+
+/// 小鸟受重力下落，同时旋转到机头朝下
+fn death_fall_system(
+    win_size: Res<WinSize>,
+    clock: Res<GameClock>,
+    mut query: Query<(&mut Velocity, &mut Transform), With<Player>>,
+) {
+    let ground_y = landing_y(&win_size);
+    for (mut velocity, mut transform) in query.iter_mut() {
+        if transform.translation.y > ground_y {
+            velocity.y =
+                (velocity.y - DEATH_GRAVITY_VELOCITY * clock.step()).max(-DEATH_MAX_FALL_VELOCITY);
+            transform.translation.y =
+                (transform.translation.y + velocity.y * clock.step()).max(ground_y);
+        } else {
+            velocity.y = 0.;
+        }
+
+        let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+        let angle = (angle - DEATH_ROTATION_SPEED * clock.step()).max(-FRAC_PI_2);
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
+
+/// 小鸟落地、震动结束后进入游戏结束状态
+fn death_finish_system(
+    win_size: Res<WinSize>,
+    shake: Option<Res<CameraShake>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    query: Query<&Transform, With<Player>>,
+) {
+    if shake.is_some() {
+        return;
+    }
+    let ground_y = landing_y(&win_size);
+    if query
+        .iter()
+        .all(|transform| transform.translation.y <= ground_y)
+    {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// 闪光淡出
+fn death_flash_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DeathFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in query.iter_mut() {
+        flash.0.tick(time.delta());
+        if flash.0.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_a(flash.0.percent_left());
+        }
+    }
+}
+
+/// 画面震动，幅度随时间减小，结束后摄像机回到原点
+fn camera_shake_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    shake: Option<ResMut<CameraShake>>,
+    mut query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Some(mut shake) = shake else {
+        return;
+    };
+    shake.0.tick(time.delta());
+    let offset = if shake.0.finished() {
+        commands.remove_resource::<CameraShake>();
+        Vec2::ZERO
+    } else {
+        let mut rng = thread_rng();
+        let intensity = DEATH_SHAKE_INTENSITY * shake.0.percent_left();
+        Vec2::new(
+            rng.gen_range(-intensity..=intensity),
+            rng.gen_range(-intensity..=intensity),
+        )
+    };
+    for mut transform in query.iter_mut() {
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
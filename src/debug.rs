@@ -0,0 +1,246 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::{
+        Color, Commands, DespawnRecursiveExt, Entity, Input, IntoSystemConfigs, KeyCode, Plugin,
+        Quat, Query, Res, ResMut, State, Transform, Vec2, Vec3, With, Without,
+    },
+    sprite::{Anchor, Sprite, SpriteBundle},
+    text::{Text, Text2dBundle, TextStyle},
+};
+
+use crate::{
+    components::{
+        DebugShape, DisplayDebug, Obstacle, ParallaxTile, Particle, Player, Toast, Velocity,
+        WeatherParticle,
+    },
+    constants::{
+        BIRD_IMG_SIZE, DEBUG_LINE_WIDTH, DEBUG_VELOCITY_SCALE, GROUND_IMG_SIZE, PIPE_IMG_SIZE,
+    },
+    pause::PauseState,
+    resource::{DebugOverlay, GameData, StaticAssets, WinSize},
+    state::GameState,
+};
+
+/// 调试插件
+///
+/// * 按 F3 显示或隐藏
+/// * 画出碰撞检测使用的矩形、得分线和小鸟的速度向量
+/// * 显示帧率、帧时间、各类实体的数量，以及 `GameData`、`GameState` 的值
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<DebugOverlay>()
+            .add_system(toggle_debug_system)
+            .add_systems(
+                (debug_shapes_system, debug_text_system)
+                    .after(toggle_debug_system)
+                    .distributive_run_if(|overlay: Res<DebugOverlay>| overlay.enabled),
+            );
+    }
+}
+
+/// 碰撞矩形的颜色
+const HITBOX_COLOR: Color = Color::rgba(1., 0., 0., 0.8);
+/// 得分线的颜色
+const SCORE_LINE_COLOR: Color = Color::rgba(1., 1., 0., 0.8);
+/// 速度向量的颜色
+const VELOCITY_COLOR: Color = Color::rgba(0., 1., 1., 0.9);
+
+/// F3 切换调试信息，关闭时清除所有调试图形
+fn toggle_debug_system(
+    mut commands: Commands,
+    kb: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    static_assets: Res<StaticAssets>,
+    win_size: Res<WinSize>,
+    query: Query<Entity, With<DebugShape>>,
+) {
+    if !kb.just_pressed(KeyCode::F3) {
+        return;
+    }
+    overlay.enabled = !overlay.enabled;
+    if !overlay.enabled {
+        for entity in query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: static_assets.kenney_future_font.clone(),
+                    font_size: 14.,
+                    color: Color::WHITE,
+                },
+            ),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform::from_xyz(
+                -win_size.width / 2. + 8.,
+                win_size.height / 2. - 32.,
+                36.,
+            ),
+            ..Default::default()
+        },
+        DisplayDebug,
+        DebugShape,
+    ));
+}
+
+/// 生成一条线段
+fn spawn_line(commands: &mut Commands, from: Vec2, to: Vec2, color: Color) {
+    let delta = to - from;
+    let center = (from + to) / 2.;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(delta.length(), DEBUG_LINE_WIDTH)),
+                ..Default::default()
+            },
+            transform: Transform {
+                // 在所有游戏内容之上，闪光和黑边之下
+                translation: center.extend(35.),
+                rotation: Quat::from_rotation_z(delta.y.atan2(delta.x)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DebugShape,
+    ));
+}
+
+/// 生成一个矩形的边框
+fn spawn_rect(commands: &mut Commands, center: Vec3, size: Vec2, color: Color) {
+    let min = center.truncate() - size / 2.;
+    let max = center.truncate() + size / 2.;
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+    for i in 0..corners.len() {
+        spawn_line(
+            commands,
+            corners[i],
+            corners[(i + 1) % corners.len()],
+            color,
+        );
+    }
+}
+
+/// 重新画出碰撞矩形、得分线和速度向量
+fn debug_shapes_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    shapes: Query<Entity, (With<DebugShape>, Without<DisplayDebug>)>,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    obstacle_query: Query<&Transform, With<Obstacle>>,
+) {
+    for entity in shapes.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // 与 player_collision_check_system 使用相同的大小，不考虑旋转
+    let bird_size = Vec2::from(BIRD_IMG_SIZE);
+    let pipe_size = Vec2::from(PIPE_IMG_SIZE);
+    let top = win_size.height / 2.;
+    let ground = -(win_size.height / 2. - GROUND_IMG_SIZE.1);
+
+    for transform in obstacle_query.iter() {
+        spawn_rect(
+            &mut commands,
+            transform.translation,
+            pipe_size,
+            HITBOX_COLOR,
+        );
+        // 小鸟的中心越过这条线时得分（尾巴通过管道的右边缘）
+        let x = transform.translation.x + PIPE_IMG_SIZE.0 / 2. + BIRD_IMG_SIZE.0 / 2.;
+        spawn_line(
+            &mut commands,
+            Vec2::new(x, ground),
+            Vec2::new(x, top),
+            SCORE_LINE_COLOR,
+        );
+    }
+
+    for (transform, velocity) in player_query.iter() {
+        spawn_rect(
+            &mut commands,
+            transform.translation,
+            bird_size,
+            HITBOX_COLOR,
+        );
+        let from = transform.translation.truncate();
+        let to = from + Vec2::new(velocity.x, velocity.y) * DEBUG_VELOCITY_SCALE;
+        if from != to {
+            spawn_line(&mut commands, from, to, VELOCITY_COLOR);
+        }
+    }
+}
+
+/// 统计数量的实体
+type CountedEntity<'a> = (
+    Option<&'a Player>,
+    Option<&'a Obstacle>,
+    Option<&'a Particle>,
+    Option<&'a WeatherParticle>,
+    Option<&'a ParallaxTile>,
+    Option<&'a Toast>,
+);
+
+/// 更新调试信息
+fn debug_text_system(
+    diagnostics: Res<Diagnostics>,
+    game_data: Res<GameData>,
+    game_state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    entities: Query<CountedEntity>,
+    mut query: Query<&mut Text, With<DisplayDebug>>,
+) {
+    let smoothed = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .map_or("-".to_owned(), |value| format!("{:.1}", value))
+    };
+
+    let mut counts = [0; 6];
+    for entity in entities.iter() {
+        let has = [
+            entity.0.is_some(),
+            entity.1.is_some(),
+            entity.2.is_some(),
+            entity.3.is_some(),
+            entity.4.is_some(),
+            entity.5.is_some(),
+        ];
+        for (count, has) in counts.iter_mut().zip(has) {
+            *count += has as usize;
+        }
+    }
+
+    let value = format!(
+        "FPS: {}  FRAME: {} MS\r\n\
+         ENTITIES: {}\r\n\
+         PLAYER: {}  OBSTACLE: {}  PARTICLE: {}\r\n\
+         WEATHER: {}  PARALLAX: {}  TOAST: {}\r\n\
+         STATE: {:?} / {:?}\r\n\
+         SCORE: {}  ALIVE: {}  SPAWN PIPE: {}",
+        smoothed(FrameTimeDiagnosticsPlugin::FPS),
+        smoothed(FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        entities.iter().count(),
+        counts[0],
+        counts[1],
+        counts[2],
+        counts[3],
+        counts[4],
+        counts[5],
+        game_state.0,
+        pause_state.0,
+        game_data.get_score(),
+        game_data.player_alive(),
+        game_data.need_spawn_obstacle(),
+    );
+    for mut text in query.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
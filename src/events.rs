@@ -0,0 +1,141 @@
+use bevy::{
+    prelude::{
+        DetectChanges, EventReader, EventWriter, Plugin, Res, ResMut, Resource, State, Vec3,
+    },
+    time::Time,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::FLAP_STREAK_SECONDS, pause::PauseState, replay::Replay};
+
+/// 游戏事件插件
+///
+/// * 核心玩法系统只负责发送事件，声音、特效、界面和统计等功能各自监听需要的事件
+/// * 统计每一局的数据，本局结束时随 `RunEnded` 一起发送
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<RunStarted>()
+            .add_event::<Flapped>()
+            .add_event::<PipePassed>()
+            .add_event::<BirdCrashed>()
+            .add_event::<RunEnded>()
+            .add_event::<RunRecorded>()
+            .init_resource::<RunStats>()
+            .add_system(run_stats_system);
+    }
+}
+
+/// 开始新的一局
+pub struct RunStarted;
+
+/// 小鸟飞行一次
+pub struct Flapped {
+    pub position: Vec3,
+}
+
+/// 小鸟通过一组管道
+pub struct PipePassed {
+    pub position: Vec3,
+    /// 通过时与上下管道的最小距离
+    pub clearance: f32,
+    /// 是否擦着管道通过
+    pub near_miss: bool,
+}
+
+/// 小鸟撞击
+pub struct BirdCrashed {
+    pub cause: CrashCause,
+    pub position: Vec3,
+}
+
+/// 撞击的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CrashCause {
+    /// 飞出画面上方
+    Ceiling,
+    /// 落到地面
+    Ground,
+    /// 撞到管道
+    Pipe,
+}
+
+/// 本局结束
+pub struct RunEnded {
+    pub stats: RunStats,
+}
+
+/// 一局的录制完成，`death_frame` 是回放时进入 `Dying` 的帧数
+pub struct RunRecorded {
+    pub replay: Replay,
+    pub death_frame: u32,
+}
+
+/// 当前这一局的统计
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RunStats {
+    /// 得分
+    pub score: u32,
+    /// 飞行次数
+    pub flaps: u32,
+    /// 当前连续飞行的次数，间隔超过 `FLAP_STREAK_SECONDS` 秒时重新计数
+    pub flap_streak: u32,
+    /// 本局最长的连续飞行次数
+    pub best_flap_streak: u32,
+    /// 上一次飞行后经过的时间（秒），暂停时不计时
+    since_flap: f32,
+    /// 擦着管道通过的次数
+    pub near_misses: u32,
+    /// 是否暂停过
+    pub paused: bool,
+    /// 撞击的原因
+    pub cause: Option<CrashCause>,
+    /// 使用过练习工具，成绩不计入记录和成就
+    pub practice: bool,
+}
+
+/// 统计每一局的数据
+#[allow(clippy::too_many_arguments)]
+fn run_stats_system(
+    mut started_events: EventReader<RunStarted>,
+    mut flapped_events: EventReader<Flapped>,
+    mut passed_events: EventReader<PipePassed>,
+    mut crashed_events: EventReader<BirdCrashed>,
+    pause_state: Res<State<PauseState>>,
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    mut ended_events: EventWriter<RunEnded>,
+) {
+    if !started_events.is_empty() {
+        started_events.clear();
+        *stats = RunStats::default();
+    }
+    stats.since_flap += time.delta_seconds();
+    for _ in flapped_events.iter() {
+        stats.flaps += 1;
+        stats.flap_streak = if stats.flap_streak > 0 && stats.since_flap <= FLAP_STREAK_SECONDS {
+            stats.flap_streak + 1
+        } else {
+            1
+        };
+        stats.best_flap_streak = stats.best_flap_streak.max(stats.flap_streak);
+        stats.since_flap = 0.;
+    }
+    for event in passed_events.iter() {
+        stats.score += 1;
+        if event.near_miss {
+            stats.near_misses += 1;
+        }
+    }
+    if pause_state.is_changed() && pause_state.0 == PauseState::Paused {
+        stats.paused = true;
+    }
+    if let Some(event) = crashed_events.iter().last() {
+        stats.cause = Some(event.cause);
+        ended_events.send(RunEnded {
+            stats: stats.clone(),
+        });
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::{prelude::*, sprite::collide_aabb::collide};
+
+use crate::{
+    components::{DisplayScore, Ground, Movable, Obstacle, Player, PlayerAnimationTimer, Velocity},
+    constants::*,
+    events::{BirdCrashed, CrashCause, PipePassed},
+    pause::GameplaySet,
+    resource::{GameClock, GameData, Practice, WinSize},
+    state::GameState,
+};
+
+/// 核心玩法插件
+///
+/// * 移动、碰撞、得分等玩法系统，不依赖窗口和渲染
+/// * 游戏窗口和无界面的模拟（`Simulation`）使用同一套系统
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .init_resource::<Practice>()
+            .add_systems(
+                (
+                    score_display_update_system,
+                    player_animation_system,
+                    player_score_system,
+                    movable_system,
+                    ground_move_system,
+                    player_collision_check_system,
+                )
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            .add_system(
+                invincible_clamp_system
+                    .after(player_collision_check_system)
+                    .run_if(|practice: Res<Practice>| practice.invincible)
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            // 准备状态下地面继续滚动，小鸟继续扇动翅膀
+            .add_systems(
+                (player_animation_system, ground_move_system).in_set(OnUpdate(GameState::Ready)),
+            )
+            .add_system(pipe_passed_system)
+            // 紧接着碰撞检测切换到死亡状态，下一帧不会再次撞击，每局只发送一次撞击事件
+            .add_system(
+                bird_crashed_system
+                    .after(player_collision_check_system)
+                    .in_set(OnUpdate(GameState::InGame)),
+            );
+    }
+}
+
+/// 玩家碰撞检测系统
+///
+/// * 无敌模式下撞击只记录不处理，小鸟不会飞出画面或者落到地面以下
+fn player_collision_check_system(
+    win_size: Res<WinSize>,
+    practice: Res<Practice>,
+    mut crashed_events: EventWriter<BirdCrashed>,
+    mut ignored: Local<Option<CrashCause>>,
+    obstacle_query: Query<(Entity, &Transform), With<Obstacle>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+) {
+    if let Ok((_, player_tf)) = player_query.get_single() {
+        let mut cause = None;
+        // 先进行边缘碰撞检测
+        if player_tf.translation.y >= win_size.height / 2. {
+            cause = Some(CrashCause::Ceiling);
+        } else if player_tf.translation.y <= -(win_size.height / 2. - GROUND_IMG_SIZE.1) {
+            cause = Some(CrashCause::Ground);
+        }
+
+        for (_, obstacle_tf) in obstacle_query.iter() {
+            let collision = collide(
+                player_tf.translation,
+                Vec2 {
+                    x: BIRD_IMG_SIZE.0,
+                    y: BIRD_IMG_SIZE.1,
+                },
+                obstacle_tf.translation,
+                Vec2 {
+                    x: PIPE_IMG_SIZE.0,
+                    y: PIPE_IMG_SIZE.1,
+                },
+            );
+            if collision.is_some() {
+                cause = Some(CrashCause::Pipe);
+                break;
+            }
+        }
+        // 判断是否已经发生碰撞
+        match cause {
+            Some(cause) if !practice.invincible => {
+                crashed_events.send(BirdCrashed {
+                    cause,
+                    position: player_tf.translation,
+                });
+            }
+            // 无敌模式下持续撞击时只记录一次
+            Some(cause) if *ignored != Some(cause) => {
+                info!("无敌模式，忽略撞击 {:?}", cause);
+            }
+            _ => {}
+        }
+        *ignored = cause.filter(|_| practice.invincible);
+    }
+}
+
+/// 无敌模式下小鸟停在画面上边缘和地面上
+fn invincible_clamp_system(
+    win_size: Res<WinSize>,
+    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
+) {
+    let top = win_size.height / 2.;
+    let ground = -(win_size.height / 2. - GROUND_IMG_SIZE.1);
+    for (mut transform, mut velocity) in query.iter_mut() {
+        if transform.translation.y > top || transform.translation.y < ground {
+            transform.translation.y = transform.translation.y.clamp(ground, top);
+            velocity.y = 0.;
+        }
+    }
+}
+
+/// 玩家得分检测
+fn player_score_system(
+    mut commands: Commands,
+    mut passed_events: EventWriter<PipePassed>,
+    obstacle_query: Query<(Entity, &Transform), With<Obstacle>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+) {
+    if let Ok((_, player_tf)) = player_query.get_single() {
+        let mut need_add_score = false;
+        let mut clearance = f32::MAX;
+        for (entity, obstacle_tf) in obstacle_query.iter() {
+            // 鸟的 尾巴通过管道的右边缘
+            if player_tf.translation.x - BIRD_IMG_SIZE.0 / 2.
+                > obstacle_tf.translation.x + PIPE_IMG_SIZE.0 / 2.
+            {
+                // 通过的话，将需要得分记为 true 并销毁管道
+                need_add_score = true;
+                clearance = clearance.min(pipe_distance(player_tf, obstacle_tf));
+                commands.entity(entity).despawn();
+            }
+        }
+        // 判断是否需要增加得分
+        if need_add_score {
+            passed_events.send(PipePassed {
+                position: player_tf.translation,
+                clearance,
+                near_miss: clearance < NEAR_MISS_DISTANCE,
+            });
+        }
+    }
+}
+
+/// 通过管道后增加得分，并生成下一组管道
+fn pipe_passed_system(mut events: EventReader<PipePassed>, mut game_data: ResMut<GameData>) {
+    for _ in events.iter() {
+        game_data.add_score();
+        game_data.call_obstacle_spawn();
+    }
+}
+
+/// 撞击后进入死亡动画
+fn bird_crashed_system(
+    mut events: EventReader<BirdCrashed>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if events.iter().last().is_some() {
+        next_state.set(GameState::Dying);
+    }
+}
+
+/// 小鸟与管道在 y 轴上的距离
+fn pipe_distance(player_tf: &Transform, obstacle_tf: &Transform) -> f32 {
+    let (player_y, pipe_y) = (player_tf.translation.y, obstacle_tf.translation.y);
+    if pipe_y > player_y {
+        (pipe_y - PIPE_IMG_SIZE.1 / 2.) - (player_y + BIRD_IMG_SIZE.1 / 2.)
+    } else {
+        (player_y - BIRD_IMG_SIZE.1 / 2.) - (pipe_y + PIPE_IMG_SIZE.1 / 2.)
+    }
+}
+
+/// 移动系统
+///
+/// * 不考虑正负值，只做加法，需要具体的实体通过移动的方向自行考虑正负值
+fn movable_system(clock: Res<GameClock>, mut query: Query<(&mut Transform, &Velocity, &Movable)>) {
+    for (mut transform, velocity, movable) in query.iter_mut() {
+        let x = velocity.x * clock.step();
+        let y = velocity.y * clock.step();
+        transform.translation.x += x;
+        transform.translation.y += y;
+        // 判断是否需要旋转
+        if movable.need_rotation {
+            if velocity.y > 0. {
+                transform.rotation = Quat::from_rotation_z(velocity.y / PLAYER_Y_MAX_UP_VELOCITY);
+            } else {
+                transform.rotation = Quat::from_rotation_z(velocity.y / PLAYER_Y_MAX_VELOCITY);
+            };
+        }
+    }
+}
+
+/// 地面移动组件
+fn ground_move_system(clock: Res<GameClock>, mut query: Query<(&mut Transform, &mut Ground)>) {
+    if let Ok((mut transform, mut ground)) = query.get_single_mut() {
+        ground.0 += clock.time_scale;
+        transform.translation.x = -ground.0;
+        ground.0 %= GROUND_ITEM_SIZE;
+    }
+}
+
+/// 角色动画系统
+fn player_animation_system(
+    time: Res<Time>,
+    mut query: Query<(&mut PlayerAnimationTimer, &mut TextureAtlasSprite)>,
+) {
+    for (mut timer, mut texture_atlas_sprite) in query.iter_mut() {
+        timer.0.tick(time.delta());
+        if timer.0.just_finished() {
+            let next_index = (texture_atlas_sprite.index + 1) % BIRD_ANIMATION_LEN;
+            texture_atlas_sprite.index = next_index;
+        }
+    }
+}
+
+/// 分数更新系统
+fn score_display_update_system(
+    game_data: Res<GameData>,
+    mut query: Query<&mut Text, With<DisplayScore>>,
+) {
+    for mut text in &mut query {
+        text.sections[1].value = game_data.get_score().to_string();
+    }
+}
@@ -0,0 +1,471 @@
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use bevy::{
+    log::{info, warn},
+    prelude::{
+        Color, Commands, DetectChanges, DetectChangesMut, Entity, EventReader, IntoSystemAppConfig,
+        IntoSystemConfig, OnEnter, OnExit, OnUpdate, Plugin, Query, Ref, Res, ResMut, Resource,
+        Transform, Vec3, With,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+    text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    time::{Time, Timer, TimerMode},
+};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::DisplayLeaderboard,
+    config::load_config,
+    constants::{LEADERBOARD_CONFIG_PATH, LEADERBOARD_KEY_ENV, LEADERBOARD_QUEUE_FILE_NAME},
+    events::{RunRecorded, RunStats},
+    replay::{self, Claim, Replay, Verdict},
+    resource::{Difficulty, StaticAssets, WinSize},
+    signature,
+    state::GameState,
+    storage::Storage,
+};
+
+/// 排行榜插件
+///
+/// * 每局结束时把带回放的签名记录加入队列，在后台逐条提交，离线或者服务器出错时按指数退避重试
+/// * 队列保存在本地，重新启动后继续提交
+/// * 进入菜单时获取最高分，显示在菜单上方
+/// * 签名密钥从环境变量读取，没有密钥时不启用
+/// * 回放由 `ReplayPlugin` 录制
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let mut config: LeaderboardConfig = load_config(
+            LEADERBOARD_CONFIG_PATH,
+            include_str!("../assets/leaderboard.ron"),
+        );
+        if let Ok(key) = std::env::var(LEADERBOARD_KEY_ENV) {
+            config.key = key;
+        }
+        if config.enabled && config.key.is_empty() {
+            warn!("没有设置 {}，排行榜不启用", LEADERBOARD_KEY_ENV);
+            config.enabled = false;
+        }
+        app.insert_resource(config)
+            .init_resource::<Leaderboard>()
+            .add_startup_system(load_queue_system)
+            .add_system(queue_run_system)
+            .add_system(submit_system.after(queue_run_system))
+            .add_system(fetch_system.in_schedule(OnEnter(GameState::Menu)))
+            .add_system(poll_fetch_system)
+            .add_system(leaderboard_display_system.in_schedule(OnEnter(GameState::Menu)))
+            .add_system(leaderboard_text_update_system.in_set(OnUpdate(GameState::Menu)))
+            .add_system(leaderboard_exit_system.in_schedule(OnExit(GameState::Menu)));
+    }
+}
+
+/// 排行榜配置
+#[derive(Resource, Debug, Clone, Deserialize)]
+pub struct LeaderboardConfig {
+    /// 是否提交成绩和获取排行榜
+    pub enabled: bool,
+    /// 服务器地址，格式为 `host:port`
+    pub address: String,
+    /// 提交时使用的玩家名称
+    pub player: String,
+    /// 签名使用的密钥，需要和服务器一致，不写在配置文件中
+    #[serde(default)]
+    pub key: String,
+    /// 菜单上显示的名次数量
+    pub top: usize,
+    /// 连接和读写的超时（秒）
+    pub timeout_seconds: f32,
+    /// 第一次重试前等待的时间（秒），之后每次加倍
+    pub retry_seconds: f32,
+    /// 重试等待的最长时间（秒）
+    pub max_retry_seconds: f32,
+}
+
+/// 提交到排行榜的一局记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    pub player: String,
+    pub score: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub version: String,
+    /// 这一局的种子和输入，服务器重新模拟验证得分
+    pub replay: Option<Replay>,
+    /// 回放时撞击的帧数
+    pub death_frame: u32,
+}
+
+impl ScoreRecord {
+    /// 用附带的回放重新模拟，检查得分和撞击帧数，回放的种子和难度必须和记录一致
+    pub fn verify_replay(&self, max_frames: u32) -> Verdict {
+        let claimed = Claim {
+            score: self.score,
+            death_frame: self.death_frame,
+        };
+        let reason = match &self.replay {
+            None => "记录不带回放",
+            Some(replay) if replay.seed != self.seed || replay.difficulty != self.difficulty => {
+                "回放的种子或难度和记录不一致"
+            }
+            Some(replay) => return replay::verify(replay, claimed, max_frames),
+        };
+        Verdict {
+            valid: false,
+            claimed,
+            actual: None,
+            reason: Some(reason.to_owned()),
+        }
+    }
+}
+
+/// 带签名的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRecord {
+    pub record: ScoreRecord,
+    /// 记录序列化为 JSON 后的 HMAC-SHA256 签名
+    pub signature: String,
+}
+
+impl SignedRecord {
+    pub fn new(record: ScoreRecord, key: &str) -> Self {
+        let signature = signature::sign(key, &Self::payload(&record));
+        Self { record, signature }
+    }
+
+    /// 签名是否有效
+    pub fn verify(&self, key: &str) -> bool {
+        signature::verify(key, &Self::payload(&self.record), &self.signature)
+    }
+
+    fn payload(record: &ScoreRecord) -> String {
+        serde_json::to_string(record).expect("记录可以序列化")
+    }
+}
+
+/// 排行榜上的一条
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub score: u32,
+    pub difficulty: Difficulty,
+}
+
+/// 提交后服务器返回的名次
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmitResponse {
+    pub rank: u32,
+}
+
+/// 请求失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// 连接失败、超时或者服务器出错，稍后重试
+    Offline(String),
+    /// 服务器拒绝了请求（签名错误、格式错误），重试也不会成功
+    Rejected(String),
+}
+
+/// 提交一条记录，返回名次
+pub fn submit(config: &LeaderboardConfig, record: &SignedRecord) -> Result<u32, RequestError> {
+    let body = serde_json::to_string(record).expect("记录可以序列化");
+    let response: SubmitResponse = request(config, "POST", "/scores", Some(&body))?;
+    Ok(response.rank)
+}
+
+/// 获取前 `limit` 名
+pub fn fetch_top(
+    config: &LeaderboardConfig,
+    limit: usize,
+) -> Result<Vec<LeaderboardEntry>, RequestError> {
+    request(config, "GET", &format!("/scores?limit={}", limit), None)
+}
+
+/// 发送 HTTP/1.1 请求并解析 JSON 响应
+fn request<T: serde::de::DeserializeOwned>(
+    config: &LeaderboardConfig,
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+) -> Result<T, RequestError> {
+    let offline = |err: std::io::Error| RequestError::Offline(err.to_string());
+    let timeout = Duration::from_secs_f32(config.timeout_seconds);
+    let address = config
+        .address
+        .to_socket_addrs()
+        .map_err(offline)?
+        .next()
+        .ok_or_else(|| RequestError::Offline(format!("无法解析地址 {}", config.address)))?;
+    let mut stream = TcpStream::connect_timeout(&address, timeout).map_err(offline)?;
+    stream.set_read_timeout(Some(timeout)).map_err(offline)?;
+    stream.set_write_timeout(Some(timeout)).map_err(offline)?;
+
+    let body = body.unwrap_or_default();
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        config.address,
+        body.len(),
+        body
+    );
+    stream.write_all(request.as_bytes()).map_err(offline)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(offline)?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| RequestError::Offline("响应格式错误".to_owned()))?;
+    let status: u16 = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| RequestError::Offline("响应格式错误".to_owned()))?;
+    match status {
+        200..=299 => serde_json::from_str(body)
+            .map_err(|err| RequestError::Rejected(format!("响应格式错误: {}", err))),
+        400..=499 => Err(RequestError::Rejected(format!("{} {}", status, body))),
+        _ => Err(RequestError::Offline(format!("{} {}", status, body))),
+    }
+}
+
+/// 排行榜的状态
+#[derive(Resource)]
+pub struct Leaderboard {
+    /// 最近一次获取的最高分
+    pub top: Vec<LeaderboardEntry>,
+    /// 最近一次请求是否成功
+    pub online: bool,
+    /// 等待提交的记录
+    queue: VecDeque<SignedRecord>,
+    submitting: Option<Task<Result<u32, RequestError>>>,
+    fetching: Option<Task<Result<Vec<LeaderboardEntry>, RequestError>>>,
+    /// 下一次提交前的等待
+    retry: Timer,
+    retry_seconds: f32,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            top: vec![],
+            online: false,
+            queue: VecDeque::new(),
+            submitting: None,
+            fetching: None,
+            retry: Timer::from_seconds(0., TimerMode::Once),
+            retry_seconds: 0.,
+        }
+    }
+}
+
+impl Leaderboard {
+    /// 等待提交的记录
+    pub fn queue(&self) -> &VecDeque<SignedRecord> {
+        &self.queue
+    }
+
+    /// 是否有正在进行的请求
+    pub fn busy(&self) -> bool {
+        self.submitting.is_some() || self.fetching.is_some()
+    }
+}
+
+/// 读取上次没有提交成功的记录
+fn load_queue_system(storage: Res<Storage>, mut leaderboard: ResMut<Leaderboard>) {
+    let queue: Vec<SignedRecord> = storage.load(LEADERBOARD_QUEUE_FILE_NAME);
+    leaderboard.queue.extend(queue);
+}
+
+/// 每局录制完成时带上回放签名并加入队列，练习的一局不提交
+fn queue_run_system(
+    mut recorded_events: EventReader<RunRecorded>,
+    config: Res<LeaderboardConfig>,
+    stats: Res<RunStats>,
+    storage: Res<Storage>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    let Some(event) = recorded_events.iter().last() else {
+        return;
+    };
+    if !config.enabled || stats.practice {
+        return;
+    }
+
+    let record = ScoreRecord {
+        player: config.player.clone(),
+        score: stats.score,
+        seed: event.replay.seed,
+        difficulty: event.replay.difficulty,
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        replay: Some(event.replay.clone()),
+        death_frame: event.death_frame,
+    };
+    leaderboard
+        .queue
+        .push_back(SignedRecord::new(record, &config.key));
+    storage.save(LEADERBOARD_QUEUE_FILE_NAME, &leaderboard.queue);
+}
+
+/// 逐条提交队列中的记录，失败时等待一段时间再重试
+fn submit_system(
+    time: Res<Time>,
+    config: Res<LeaderboardConfig>,
+    storage: Res<Storage>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    if !config.enabled {
+        return;
+    }
+    // 只有最高分和在线状态需要触发界面更新
+    let board = leaderboard.bypass_change_detection();
+
+    if let Some(task) = &mut board.submitting {
+        let Some(result) = future::block_on(future::poll_once(task)) else {
+            return;
+        };
+        board.submitting = None;
+        let online = match result {
+            Ok(rank) => {
+                info!("成绩已提交，排名第 {}", rank);
+                board.queue.pop_front();
+                true
+            }
+            Err(RequestError::Rejected(err)) => {
+                warn!("服务器拒绝了成绩，不再重试: {}", err);
+                board.queue.pop_front();
+                true
+            }
+            Err(RequestError::Offline(err)) => {
+                board.retry_seconds = (board.retry_seconds * 2.)
+                    .clamp(config.retry_seconds, config.max_retry_seconds);
+                board.retry = Timer::from_seconds(board.retry_seconds, TimerMode::Once);
+                warn!("提交成绩失败，{:.0} 秒后重试: {}", board.retry_seconds, err);
+                false
+            }
+        };
+        // 服务器给出明确的答复后清除退避，下一条记录立即提交
+        if online {
+            board.retry_seconds = 0.;
+            board.retry = Timer::from_seconds(0., TimerMode::Once);
+        }
+        storage.save(LEADERBOARD_QUEUE_FILE_NAME, &board.queue);
+        if board.online != online {
+            leaderboard.online = online;
+        }
+        return;
+    }
+
+    // 重试等待使用真实时间，暂停时也继续
+    board.retry.tick(time.raw_delta());
+    if !board.retry.finished() {
+        return;
+    }
+    if let Some(record) = board.queue.front() {
+        let (config, record) = (config.clone(), record.clone());
+        board.submitting =
+            Some(AsyncComputeTaskPool::get().spawn(async move { submit(&config, &record) }));
+    }
+}
+
+/// 获取最高分
+fn fetch_system(config: Res<LeaderboardConfig>, mut leaderboard: ResMut<Leaderboard>) {
+    if !config.enabled || leaderboard.fetching.is_some() {
+        return;
+    }
+    let config = config.clone();
+    leaderboard.bypass_change_detection().fetching =
+        Some(AsyncComputeTaskPool::get().spawn(async move { fetch_top(&config, config.top) }));
+}
+
+/// 获取完成后更新最高分
+fn poll_fetch_system(mut leaderboard: ResMut<Leaderboard>) {
+    let board = leaderboard.bypass_change_detection();
+    let Some(task) = &mut board.fetching else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    board.fetching = None;
+    match result {
+        Ok(top) => {
+            leaderboard.top = top;
+            leaderboard.online = true;
+        }
+        Err(err) => {
+            warn!("获取排行榜失败: {:?}", err);
+            leaderboard.online = false;
+        }
+    }
+}
+
+/// 在菜单上方显示排行榜
+fn leaderboard_display_system(
+    mut commands: Commands,
+    config: Res<LeaderboardConfig>,
+    win_size: Res<WinSize>,
+) {
+    if !config.enabled {
+        return;
+    }
+    commands.spawn((
+        Text2dBundle {
+            text: Text::default().with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(0., win_size.height / 2. - 90., 4.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DisplayLeaderboard,
+    ));
+}
+
+/// 刚显示或者排行榜变化时更新文字
+fn leaderboard_text_update_system(
+    leaderboard: Res<Leaderboard>,
+    static_assets: Res<StaticAssets>,
+    mut query: Query<(Ref<DisplayLeaderboard>, &mut Text)>,
+) {
+    let Ok((display, mut text)) = query.get_single_mut() else {
+        return;
+    };
+    if !display.is_added() && !leaderboard.is_changed() {
+        return;
+    }
+
+    let style = TextStyle {
+        font: static_assets.kenney_future_font.clone(),
+        font_size: 18.,
+        color: Color::DARK_GRAY,
+    };
+    let mut lines = vec![if leaderboard.online {
+        "TOP SCORES".to_owned()
+    } else {
+        "TOP SCORES (OFFLINE)".to_owned()
+    }];
+    lines.extend(leaderboard.top.iter().enumerate().map(|(index, entry)| {
+        format!(
+            "{}. {} {} {}",
+            index + 1,
+            entry.player,
+            entry.score,
+            entry.difficulty.label()
+        )
+    }));
+    text.sections = vec![TextSection::new(lines.join("\n"), style)];
+}
+
+/// 离开菜单时移除排行榜
+fn leaderboard_exit_system(mut commands: Commands, query: Query<Entity, With<DisplayLeaderboard>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
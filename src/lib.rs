@@ -0,0 +1,35 @@
+//! Flappy Bird 游戏的全部插件和系统
+//!
+//! * `main.rs` 负责创建窗口、加载资源并运行游戏
+//! * `simulation` 不需要窗口和渲染，可以逐帧推进游戏，供测试使用
+
+pub mod achievement;
+pub mod audio;
+pub mod benchmark;
+pub mod bot;
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod constants;
+pub mod death;
+pub mod debug;
+pub mod events;
+pub mod gameplay;
+pub mod leaderboard;
+pub mod music;
+pub mod obstacle;
+pub mod parallax;
+pub mod particle;
+pub mod pause;
+pub mod player;
+pub mod practice;
+pub mod replay;
+pub mod resource;
+pub mod run_log;
+pub mod settings;
+pub mod signature;
+pub mod simulation;
+pub mod state;
+pub mod storage;
+pub mod theme;
+pub mod window;
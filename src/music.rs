@@ -0,0 +1,231 @@
+use bevy::{
+    asset::LoadState,
+    audio::AudioSinkPlayback,
+    prelude::{
+        AssetServer, Assets, Audio, AudioSink, AudioSource, DetectChanges, Handle,
+        IntoSystemConfig, PlaybackSettings, Plugin, Res, ResMut, Resource, State,
+    },
+    time::Time,
+};
+use serde::Deserialize;
+
+use crate::{
+    audio::{AudioChannel, AudioMixer},
+    config::load_config,
+    constants::MUSIC_CONFIG_PATH,
+    pause::PauseState,
+    state::GameState,
+};
+
+/// 背景音乐插件
+///
+/// * 根据游戏状态切换曲目，切换时交叉淡入淡出
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let config: MusicConfig =
+            load_config(MUSIC_CONFIG_PATH, include_str!("../assets/music.ron"));
+        app.insert_resource(config)
+            .init_resource::<MusicPlayer>()
+            .add_system(music_transition_system)
+            .add_system(music_fade_system.after(music_transition_system));
+    }
+}
+
+/// 背景音乐配置，路径相对于 assets 目录
+#[derive(Resource, Deserialize)]
+pub struct MusicConfig {
+    /// 菜单和设置页面的音乐
+    menu: Option<String>,
+    /// 游戏中的音乐
+    in_game: Option<String>,
+    /// 暂停时的音乐，没有配置时沿用游戏中的音乐
+    paused: Option<String>,
+    /// 游戏结束时只播放一次的音乐
+    game_over: Option<String>,
+    /// 交叉淡入淡出的时长（秒）
+    crossfade_seconds: f32,
+}
+
+/// 状态切换时对音乐的处理
+enum MusicCue<'a> {
+    /// 继续播放当前曲目
+    Keep,
+    /// 淡出当前曲目
+    Stop,
+    /// 切换到新曲目
+    Play { path: &'a str, looped: bool },
+}
+
+impl MusicConfig {
+    fn cue(&self, state: &GameState, pause_state: &PauseState) -> MusicCue<'_> {
+        if *state == GameState::InGame && *pause_state != PauseState::Running {
+            return match &self.paused {
+                Some(_) => MusicCue::from_track(&self.paused, true),
+                None => MusicCue::Keep,
+            };
+        }
+        match state {
+            GameState::Menu | GameState::Settings | GameState::Achievements => {
+                MusicCue::from_track(&self.menu, true)
+            }
+            GameState::Ready | GameState::InGame => MusicCue::from_track(&self.in_game, true),
+            // 死亡动画期间继续播放游戏中的音乐
+            GameState::Dying => MusicCue::Keep,
+            GameState::GameOver => MusicCue::from_track(&self.game_over, false),
+        }
+    }
+}
+
+impl<'a> MusicCue<'a> {
+    fn from_track(track: &'a Option<String>, looped: bool) -> Self {
+        match track {
+            Some(path) => MusicCue::Play { path, looped },
+            None => MusicCue::Stop,
+        }
+    }
+}
+
+/// 正在播放的曲目
+struct MusicTrack {
+    path: String,
+    looped: bool,
+    source: Handle<AudioSource>,
+    sink: Handle<AudioSink>,
+    /// 淡入淡出进度 0 ~ 1
+    fade: f32,
+}
+
+/// 背景音乐播放器
+#[derive(Resource, Default)]
+pub struct MusicPlayer {
+    current: Option<MusicTrack>,
+    fading_out: Vec<MusicTrack>,
+}
+
+impl MusicPlayer {
+    fn fade_out_current(&mut self) {
+        if let Some(track) = self.current.take() {
+            self.fading_out.push(track);
+        }
+    }
+}
+
+/// 游戏状态变化时切换曲目
+fn music_transition_system(
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    config: Res<MusicConfig>,
+    asset_server: Res<AssetServer>,
+    audio_player: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut player: ResMut<MusicPlayer>,
+) {
+    if !state.is_changed() && !pause_state.is_changed() {
+        return;
+    }
+    match config.cue(&state.0, &pause_state.0) {
+        MusicCue::Keep => (),
+        MusicCue::Stop => player.fade_out_current(),
+        MusicCue::Play { path, looped } => {
+            if let Some(current) = &player.current {
+                if current.path == path {
+                    return;
+                }
+            }
+            player.fade_out_current();
+            // 还在淡出的循环曲目直接淡入回来，不从头播放
+            let fading = player
+                .fading_out
+                .iter()
+                .position(|track| track.looped && looped && track.path == path);
+            let track = match fading {
+                Some(index) => player.fading_out.remove(index),
+                None => {
+                    let settings = if looped {
+                        PlaybackSettings::LOOP
+                    } else {
+                        PlaybackSettings::ONCE
+                    };
+                    let source = asset_server.load(path);
+                    let sink =
+                        audio_player.play_with_settings(source.clone(), settings.with_volume(0.));
+                    MusicTrack {
+                        path: path.to_owned(),
+                        looped,
+                        source,
+                        sink: audio_sinks.get_handle(sink),
+                        fade: 0.,
+                    }
+                }
+            };
+            player.current = Some(track);
+        }
+    }
+}
+
+/// 淡入淡出并按混音器的音乐通道设置音量
+fn music_fade_system(
+    time: Res<Time>,
+    config: Res<MusicConfig>,
+    asset_server: Res<AssetServer>,
+    mixer: Res<AudioMixer>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut player: ResMut<MusicPlayer>,
+) {
+    // 使用真实时间，暂停时也能正常淡入淡出
+    let step = if config.crossfade_seconds > 0. {
+        time.raw_delta_seconds() / config.crossfade_seconds
+    } else {
+        1.
+    };
+    let volume = mixer.volume(AudioChannel::Music);
+
+    if let Some(track) = &mut player.current {
+        track.fade = (track.fade + step).min(1.);
+        if let Some(sink) = audio_sinks.get(&track.sink) {
+            sink.set_volume(volume * track.fade);
+        }
+    }
+    player.fading_out.retain_mut(|track| {
+        track.fade = (track.fade - step).max(0.);
+        // 声音还在加载时保留句柄，避免开始播放后无法停止；加载失败的曲目永远不会播放，直接丢弃
+        let Some(sink) = audio_sinks.get(&track.sink) else {
+            return asset_server.get_load_state(&track.source) != LoadState::Failed;
+        };
+        if track.fade <= 0. {
+            sink.stop();
+            return false;
+        }
+        sink.set_volume(volume * track.fade);
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::States;
+
+    use super::*;
+
+    #[test]
+    fn every_state_has_music() {
+        let config: MusicConfig = ron::from_str(include_str!("../assets/music.ron")).unwrap();
+        for state in GameState::variants() {
+            for pause_state in PauseState::variants() {
+                match config.cue(&state, &pause_state) {
+                    MusicCue::Stop => panic!("{:?} {:?} 没有配置音乐", state, pause_state),
+                    MusicCue::Play { path, .. } => {
+                        assert!(
+                            std::path::Path::new("assets").join(path).is_file(),
+                            "{} 不存在",
+                            path
+                        );
+                    }
+                    MusicCue::Keep => (),
+                }
+            }
+        }
+    }
+}
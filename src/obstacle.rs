@@ -1,23 +1,24 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use std::time::Duration;
 
 use crate::{
     components::{Movable, Obstacle, Velocity},
     constants::{
-        BACKGROUND_IMG_SIZE, GAP_MAX, GAP_MIN, GROUND_IMG_SIZE, PIPE_IMG_SIZE,
-        PLAYER_X_MAX_VELOCITY, SPAWN_OBSTACLE_TICK,
+        GAP_EDGE_MARGIN, GAP_MAX, GAP_MIN, GROUND_IMG_SIZE, PIPE_IMG_SIZE, PLAYER_X_MAX_VELOCITY,
+        SPAWN_OBSTACLE_TICK,
     },
-    resource::{GameData, StaticAssets, WinSize},
+    events::RunStarted,
+    pause::GameplaySet,
+    resource::{GameClock, GameData, GameRng, ObstacleSpawnTimer, Settings, StaticAssets, WinSize},
     state::GameState,
 };
 
 use bevy::{
     prelude::{
-        Commands, Entity, IntoSystemAppConfig, IntoSystemConfig, OnEnter, OnUpdate, Plugin, Query,
-        Res, ResMut, Transform, Vec3, With,
+        Commands, Entity, EventReader, IntoSystemAppConfig, IntoSystemConfig, OnEnter, OnUpdate,
+        Plugin, Query, Res, ResMut, Transform, Vec3, With,
     },
     sprite::{Sprite, SpriteBundle},
-    time::common_conditions::on_timer,
 };
 
 /// 障碍物插件
@@ -25,152 +26,171 @@ pub struct ObstaclePlugin;
 
 impl Plugin for ObstaclePlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(obstacle_init_system.in_schedule(OnEnter(GameState::InGame)))
+        app.init_resource::<GameRng>()
+            .init_resource::<ObstacleSpawnTimer>()
+            .add_system(reseed_system)
+            .add_system(obstacle_init_system.in_schedule(OnEnter(GameState::InGame)))
             .add_system(
                 spawn_obstacle_system
-                    .run_if(on_timer(Duration::from_secs_f32(0.2)))
+                    .in_set(GameplaySet)
                     .in_set(OnUpdate(GameState::InGame)),
             );
     }
 }
 
+/// 一组管道之间的可通过区域
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeGap {
+    /// 可通过区域的下边缘，也是下方管道的顶部
+    pub bottom: f32,
+    /// 可通过区域的上边缘，也是上方管道的底部
+    pub top: f32,
+}
+
+impl PipeGap {
+    /// 上方管道中心点的 y 坐标
+    pub fn upper_pipe_y(&self) -> f32 {
+        self.top + PIPE_IMG_SIZE.1 / 2.
+    }
+
+    /// 下方管道中心点的 y 坐标
+    pub fn lower_pipe_y(&self) -> f32 {
+        self.bottom - PIPE_IMG_SIZE.1 / 2.
+    }
+}
+
+/// 可通过区域的最大值，得分越高越小，不小于 `GAP_MIN`
+pub fn max_gap_size(score: u8, gap_scale: f32) -> f32 {
+    // 这里也可以做些其他的判断。改变下别的数据。比如说 让管道的移动速度变快！
+    (GAP_MAX * gap_scale - score as f32 / 10.).max(GAP_MIN)
+}
+
+/// 随机生成一组管道的可通过区域
+///
+/// * 只依赖传入的参数和随机数，相同的随机数得到相同的结果
+/// * 可通过区域不小于 `GAP_MIN`，不大于 `max_gap_size`，并且完全在地面和画面上边缘之间（各留出 `GAP_EDGE_MARGIN`）
+pub fn pipe_gap(rng: &mut impl Rng, win_height: f32, score: u8, gap_scale: f32) -> PipeGap {
+    // 可以飞行的区域，地面以上，画面上边缘以下
+    let ceiling = win_height / 2. - GAP_EDGE_MARGIN;
+    let floor = -(win_height / 2. - GROUND_IMG_SIZE.1) + GAP_EDGE_MARGIN;
+    // 管道区域的中心点。因为要排除地面的高度
+    let center_y = (ceiling + floor) / 2.;
+
+    // y轴 随机中心点
+    // 定义合理范围
+    let reasonable_y_max = win_height / 2. - 100.;
+    let reasonable_y_min = -(win_height / 2. - 100. - GROUND_IMG_SIZE.1);
+    // 随机可通过区域的中心点
+    let point_y = rng.gen_range(reasonable_y_min..reasonable_y_max);
+    let half_distance = (center_y - point_y).abs() / 2.;
+
+    // 根据得分获取一个随机的可通过区域的大小
+    let max = max_gap_size(score, gap_scale);
+    let gap = if max > GAP_MIN {
+        rng.gen_range(GAP_MIN..max)
+    } else {
+        GAP_MIN
+    };
+    let rand_half_gap = gap * rng.gen_range(0.3..0.7);
+
+    // 通过中心点和可通过区域计算上下边缘
+    let mut top = center_y + half_distance + rand_half_gap;
+    let mut bottom = center_y - half_distance - (gap - rand_half_gap);
+    // 中心点离得越远上下边缘分得越开，超过最大值时以原来的中间为中心缩小
+    if top - bottom > max {
+        let middle = (top + bottom) / 2.;
+        top = middle + max / 2.;
+        bottom = middle - max / 2.;
+    }
+    // 超出可以飞行的区域时整体移回区域内，仍然放不下时缩小
+    if top > ceiling {
+        bottom -= top - ceiling;
+        top = ceiling;
+    }
+    if bottom < floor {
+        top = (top + floor - bottom).min(ceiling);
+        bottom = floor;
+    }
+    PipeGap { bottom, top }
+}
+
+/// 每一局开始时更换管道的种子
+fn reseed_system(mut started_events: EventReader<RunStarted>, mut rng: ResMut<GameRng>) {
+    if !started_events.is_empty() {
+        started_events.clear();
+        rng.start_run();
+    }
+}
+
 /// 障碍物初始化
+#[allow(clippy::too_many_arguments)]
 fn obstacle_init_system(
     mut commands: Commands,
     static_assets: Res<StaticAssets>,
     win_size: Res<WinSize>,
     game_data: Res<GameData>,
+    settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
+    mut spawn_timer: ResMut<ObstacleSpawnTimer>,
     query: Query<Entity, With<Obstacle>>,
 ) {
+    spawn_timer.0.reset();
     let count = query.iter().count();
     if count >= 4 {
         return;
     }
 
-    let mut rng = thread_rng();
     // 初始 x 坐标
     let x = win_size.width / 2. + PIPE_IMG_SIZE.0 / 2.;
-    // 初始化 管道区域的中心点。因为要排除地面的高度
-    let center_y = (win_size.height - BACKGROUND_IMG_SIZE.1) / 2.;
-    // 定义合理范围
-    let reasonable_y_max = win_size.height / 2. - 100.;
-    let reasonable_y_min = -(win_size.height / 2. - 100. - GROUND_IMG_SIZE.1);
-
     let size = SPAWN_OBSTACLE_TICK * PLAYER_X_MAX_VELOCITY;
 
     for i in 0..2 {
         let x = x - PIPE_IMG_SIZE.0 - size * i as f32;
-        // y轴 随机中心点
-        // 随机可通过区域的中心点
-        let point_y = rng.gen_range(reasonable_y_min..reasonable_y_max);
-        let half_distance = (center_y - point_y).abs() / 2.;
-
-        // 获取得分 ， 并根据得分获取一个随机的可通过区域的大小
-        let score = game_data.get_score();
-        let max = GAP_MAX - score as f32 / 10.;
-        // 不让 max 小于最小值
-        // 这里也可以做些其他的判断。改变下别的数据。比如说 让管道的移动速度变快！
-        let max = max.max(GAP_MIN);
-        let min = GAP_MIN;
-        let gap = rng.gen_range(min.min(max)..min.max(max));
-        let rand_half_gap = gap * rng.gen_range(0.3..0.7);
-        // 通过中心点，可通过区域，以及管道的高来计算 上下两个管道各自中心点的 y 坐标
-        let half_pipe = PIPE_IMG_SIZE.1 / 2.;
-        let pipe_upper = center_y + half_distance + (rand_half_gap + half_pipe);
-        let pipe_down = center_y - half_distance - (gap - rand_half_gap + half_pipe);
-
-        // 下方水管
-        commands.spawn((
-            SpriteBundle {
-                texture: static_assets.pipe.clone(),
-                transform: Transform {
-                    translation: Vec3 {
-                        x,
-                        y: pipe_down,
-                        z: 2.,
-                    },
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            Velocity {
-                x: -PLAYER_X_MAX_VELOCITY,
-                y: 0.,
-            },
-            Movable {
-                need_rotation: false,
-            },
-            Obstacle,
-        ));
-
-        // 上方水管
-        commands.spawn((
-            SpriteBundle {
-                texture: static_assets.pipe.clone(),
-                transform: Transform {
-                    translation: Vec3 {
-                        x,
-                        y: pipe_upper,
-                        z: 2.,
-                    },
-                    ..Default::default()
-                },
-                sprite: Sprite {
-                    flip_y: true,
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            Velocity {
-                x: -PLAYER_X_MAX_VELOCITY,
-                y: 0.,
-            },
-            Movable {
-                need_rotation: false,
-            },
-            Obstacle,
-        ));
+        let gap = pipe_gap(
+            &mut rng.rng,
+            win_size.height,
+            game_data.get_score(),
+            settings.difficulty.gap_scale(),
+        );
+        spawn_pipe_pair(&mut commands, &static_assets, x, gap);
     }
 }
 
+/// 每隔 `SPAWN_OBSTACLE_CHECK_SECONDS` 秒检查一次是否需要生成管道
+#[allow(clippy::too_many_arguments)]
 fn spawn_obstacle_system(
     mut commands: Commands,
     mut game_data: ResMut<GameData>,
+    settings: Res<Settings>,
     static_assets: Res<StaticAssets>,
     win_size: Res<WinSize>,
+    clock: Res<GameClock>,
+    mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if !game_data.need_spawn_obstacle() {
+    let step = Duration::from_secs_f32(clock.step());
+    if !spawn_timer.0.tick(step).just_finished() || !game_data.need_spawn_obstacle() {
         return;
     }
     game_data.obstacle_call_back();
-    let mut rng = thread_rng();
     // 初始 x 坐标
     let x = win_size.width / 2. + PIPE_IMG_SIZE.0 / 2.;
-    // 初始化 管道区域的中心点。因为要排除地面的高度
-    let center_y = (win_size.height - BACKGROUND_IMG_SIZE.1) / 2.;
-
-    // y轴 随机中心点
-    // 定义合理范围
-    let reasonable_y_max = win_size.height / 2. - 100.;
-    let reasonable_y_min = -(win_size.height / 2. - 100. - GROUND_IMG_SIZE.1);
-    // 随机可通过区域的中心点
-    let point_y = rng.gen_range(reasonable_y_min..reasonable_y_max);
-    let half_distance = (center_y - point_y).abs() / 2.;
-
-    // 获取得分 ， 并根据得分获取一个随机的可通过区域的大小
-    let score = game_data.get_score();
-    let max = GAP_MAX - score as f32 / 10.;
-    // 不让 max 小于最小值
-    // 这里也可以做些其他的判断。改变下别的数据。比如说 让管道的移动速度变快！
-    let max = max.max(GAP_MIN);
-    let min = GAP_MIN;
-    let gap = rng.gen_range(min.min(max)..min.max(max));
-    let rand_half_gap = gap * rng.gen_range(0.3..0.7);
-    // 通过中心点，可通过区域，以及管道的高来计算 上下两个管道各自中心点的 y 坐标
-    let half_pipe = PIPE_IMG_SIZE.1 / 2.;
-    let pipe_upper = center_y + half_distance + (rand_half_gap + half_pipe);
-    let pipe_down = center_y - half_distance - (gap - rand_half_gap + half_pipe);
+    let gap = pipe_gap(
+        &mut rng.rng,
+        win_size.height,
+        game_data.get_score(),
+        settings.difficulty.gap_scale(),
+    );
+    spawn_pipe_pair(&mut commands, &static_assets, x, gap);
+}
 
+/// 在 x 处生成上下两个管道
+pub fn spawn_pipe_pair(
+    commands: &mut Commands,
+    static_assets: &StaticAssets,
+    x: f32,
+    gap: PipeGap,
+) {
     // 下方水管
     commands.spawn((
         SpriteBundle {
@@ -178,7 +198,7 @@ fn spawn_obstacle_system(
             transform: Transform {
                 translation: Vec3 {
                     x,
-                    y: pipe_down,
+                    y: gap.lower_pipe_y(),
                     z: 2.,
                 },
                 ..Default::default()
@@ -202,7 +222,7 @@ fn spawn_obstacle_system(
             transform: Transform {
                 translation: Vec3 {
                     x,
-                    y: pipe_upper,
+                    y: gap.upper_pipe_y(),
                     z: 2.,
                 },
                 ..Default::default()
@@ -223,3 +243,79 @@ fn spawn_obstacle_system(
         Obstacle,
     ));
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{constants::WINDOW_HEIGHT, resource::Difficulty};
+
+    /// 随机测试的次数
+    const CASES: u64 = 20_000;
+
+    /// 对随机的种子、得分和难度检查 `check`
+    fn for_all_gaps(check: impl Fn(PipeGap, u8, Difficulty)) {
+        let mut params = StdRng::seed_from_u64(0);
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let score = params.gen_range(0..=u8::MAX);
+            let difficulty =
+                [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard][params.gen_range(0..3)];
+            let gap = pipe_gap(&mut rng, WINDOW_HEIGHT, score, difficulty.gap_scale());
+            check(gap, score, difficulty);
+        }
+    }
+
+    #[test]
+    fn gap_is_at_least_gap_min() {
+        for_all_gaps(|gap, score, difficulty| {
+            assert!(
+                gap.top - gap.bottom >= GAP_MIN,
+                "{gap:?} score {score} {difficulty:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn gap_is_at_most_max_gap_size() {
+        for_all_gaps(|gap, score, difficulty| {
+            assert!(
+                gap.top - gap.bottom <= max_gap_size(score, difficulty.gap_scale()) + 1e-3,
+                "{gap:?} score {score} {difficulty:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn gap_is_within_playable_area() {
+        let ground = -(WINDOW_HEIGHT / 2. - GROUND_IMG_SIZE.1);
+        for_all_gaps(|gap, score, difficulty| {
+            assert!(gap.bottom >= ground, "{gap:?} score {score} {difficulty:?}");
+            assert!(
+                gap.top <= WINDOW_HEIGHT / 2.,
+                "{gap:?} score {score} {difficulty:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn pipes_cover_the_rest_of_the_column() {
+        let ground = -(WINDOW_HEIGHT / 2. - GROUND_IMG_SIZE.1);
+        let half_pipe = PIPE_IMG_SIZE.1 / 2.;
+        for_all_gaps(|gap, score, difficulty| {
+            // 上方管道从可通过区域一直延伸到画面上边缘以外
+            assert!((gap.upper_pipe_y() - half_pipe - gap.top).abs() < 1e-3);
+            assert!(
+                gap.upper_pipe_y() + half_pipe >= WINDOW_HEIGHT / 2.,
+                "{gap:?} score {score} {difficulty:?}"
+            );
+            // 下方管道从可通过区域一直延伸到地面以下
+            assert!((gap.lower_pipe_y() + half_pipe - gap.bottom).abs() < 1e-3);
+            assert!(
+                gap.lower_pipe_y() - half_pipe <= ground,
+                "{gap:?} score {score} {difficulty:?}"
+            );
+        });
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::{
+    AssetServer, Commands, IntoSystemConfig, OnUpdate, Plugin, Query, Res, Resource, Sprite,
+    SpriteBundle, StartupSet, Transform, Vec2, Vec3,
+};
+use serde::Deserialize;
+
+use crate::{
+    components::ParallaxTile,
+    config::load_config,
+    constants::{LAYERS_CONFIG_PATH, PLAYER_X_MAX_VELOCITY},
+    pause::GameplaySet,
+    resource::{GameClock, WinSize},
+    state::GameState,
+};
+
+/// 视差背景插件
+///
+/// * 每个图层横向平铺，并按自己的速度循环滚动
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let manifest: LayerManifest =
+            load_config(LAYERS_CONFIG_PATH, include_str!("../assets/layers.ron"));
+        app.insert_resource(manifest)
+            .add_startup_system(spawn_layers_system.in_base_set(StartupSet::PostStartup))
+            .add_system(
+                parallax_move_system
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            // 准备状态下和地面一起继续滚动
+            .add_system(parallax_move_system.in_set(OnUpdate(GameState::Ready)));
+    }
+}
+
+/// 图层清单
+#[derive(Resource, Deserialize)]
+pub struct LayerManifest {
+    layers: Vec<LayerConfig>,
+}
+
+impl LayerManifest {
+    /// 图层原本的图片
+    pub fn image(&self, name: &str) -> Option<&str> {
+        self.layers
+            .iter()
+            .find(|layer| layer.name == name)
+            .map(|layer| layer.image.as_str())
+    }
+}
+
+/// 单个图层的配置
+#[derive(Deserialize)]
+struct LayerConfig {
+    /// 图层名称
+    name: String,
+    /// 图片路径
+    image: String,
+    /// 单张图片的大小
+    size: (f32, f32),
+    /// 图层中心的 y 坐标
+    y: f32,
+    /// 相对管道移动速度的比例
+    speed: f32,
+}
+
+/// 生成所有图层
+fn spawn_layers_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    manifest: Res<LayerManifest>,
+    win_size: Res<WinSize>,
+) {
+    for (index, layer) in manifest.layers.iter().enumerate() {
+        let (tile_w, tile_h) = layer.size;
+        // 多铺一张，滚动时右侧不会露出空白
+        let count = (win_size.width / tile_w).ceil() as usize + 1;
+        let span = tile_w * count as f32;
+        let texture = asset_server.load(layer.image.as_str());
+        // 图层都在管道后面，越靠后的图层越远
+        let z = 1. + index as f32 * 0.1;
+        for i in 0..count {
+            let x = -win_size.width / 2. + tile_w / 2. + tile_w * i as f32;
+            commands.spawn((
+                SpriteBundle {
+                    texture: texture.clone(),
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(tile_w, tile_h)),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(x, layer.y, z),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ParallaxTile {
+                    layer: layer.name.clone(),
+                    speed: layer.speed,
+                    width: tile_w,
+                    span,
+                },
+            ));
+        }
+    }
+}
+
+/// 图层滚动系统
+fn parallax_move_system(
+    win_size: Res<WinSize>,
+    clock: Res<GameClock>,
+    mut query: Query<(&mut Transform, &ParallaxTile)>,
+) {
+    for (mut transform, tile) in query.iter_mut() {
+        transform.translation.x -= PLAYER_X_MAX_VELOCITY * tile.speed * clock.step();
+        // 完全移出左边缘后接到最右侧
+        if transform.translation.x + tile.width / 2. < -win_size.width / 2. {
+            transform.translation.x += tile.span;
+        }
+    }
+}
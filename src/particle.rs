@@ -0,0 +1,177 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::{
+        Color, Commands, Entity, EventReader, EventWriter, IntoSystemConfig, Plugin, Query, Res,
+        Sprite, SpriteBundle, Transform, Vec2, Vec3,
+    },
+    time::{Time, Timer, TimerMode},
+};
+use rand::{thread_rng, Rng};
+
+use crate::{
+    components::Particle,
+    events::{BirdCrashed, Flapped, PipePassed},
+    resource::Settings,
+};
+
+/// 粒子插件
+///
+/// * 简单的 CPU 粒子：生成、存活时间、速度、重力和淡出
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<ParticleBurst>()
+            .add_system(gameplay_particles_system.before(particle_spawn_system))
+            .add_system(particle_spawn_system)
+            .add_system(particle_update_system);
+    }
+}
+
+/// 粒子效果的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BurstKind {
+    /// 飞行时的羽毛
+    Feathers,
+    /// 得分时的闪光
+    Sparkles,
+    /// 撞击时的碎片
+    Crash,
+}
+
+/// 在指定位置生成一组粒子的事件
+pub struct ParticleBurst {
+    pub kind: BurstKind,
+    pub position: Vec3,
+}
+
+/// 一种粒子效果的参数
+struct BurstConfig {
+    count: usize,
+    colors: &'static [Color],
+    size: (f32, f32),
+    speed: (f32, f32),
+    /// 发射方向的范围（弧度）
+    angle: (f32, f32),
+    lifetime: f32,
+    gravity: f32,
+}
+
+impl BurstKind {
+    fn config(&self) -> BurstConfig {
+        match self {
+            BurstKind::Feathers => BurstConfig {
+                count: 6,
+                colors: &[Color::WHITE, Color::ANTIQUE_WHITE],
+                size: (3., 5.),
+                speed: (20., 60.),
+                // 主要向后下方飘落
+                angle: (0.6 * TAU, 0.85 * TAU),
+                lifetime: 0.6,
+                gravity: -60.,
+            },
+            BurstKind::Sparkles => BurstConfig {
+                count: 12,
+                colors: &[Color::YELLOW, Color::GOLD, Color::WHITE],
+                size: (2., 4.),
+                speed: (40., 100.),
+                angle: (0., TAU),
+                lifetime: 0.5,
+                gravity: 0.,
+            },
+            BurstKind::Crash => BurstConfig {
+                count: 24,
+                colors: &[Color::ORANGE, Color::YELLOW, Color::WHITE],
+                size: (3., 6.),
+                speed: (80., 200.),
+                angle: (0., TAU),
+                lifetime: 0.8,
+                gravity: -300.,
+            },
+        }
+    }
+}
+
+/// 游戏事件对应的粒子效果
+fn gameplay_particles_system(
+    mut flapped_events: EventReader<Flapped>,
+    mut passed_events: EventReader<PipePassed>,
+    mut crashed_events: EventReader<BirdCrashed>,
+    mut bursts: EventWriter<ParticleBurst>,
+) {
+    let flaps = flapped_events.iter().map(|event| ParticleBurst {
+        kind: BurstKind::Feathers,
+        position: event.position,
+    });
+    let passes = passed_events.iter().map(|event| ParticleBurst {
+        kind: BurstKind::Sparkles,
+        position: event.position,
+    });
+    let crashes = crashed_events.iter().map(|event| ParticleBurst {
+        kind: BurstKind::Crash,
+        position: event.position,
+    });
+    bursts.send_batch(flaps.chain(passes).chain(crashes));
+}
+
+/// 生成粒子
+fn particle_spawn_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut events: EventReader<ParticleBurst>,
+) {
+    if !settings.effects {
+        events.clear();
+        return;
+    }
+    let mut rng = thread_rng();
+    for burst in events.iter() {
+        let config = burst.kind.config();
+        for _ in 0..config.count {
+            let angle = rng.gen_range(config.angle.0..config.angle.1);
+            let speed = rng.gen_range(config.speed.0..config.speed.1);
+            let size = rng.gen_range(config.size.0..config.size.1);
+            let color = config.colors[rng.gen_range(0..config.colors.len())];
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(size)),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        // 显示在小鸟和管道前面
+                        translation: burst.position.truncate().extend(5.),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Particle {
+                    velocity: Vec2::from_angle(angle) * speed,
+                    gravity: config.gravity,
+                    lifetime: Timer::from_seconds(config.lifetime, TimerMode::Once),
+                },
+            ));
+        }
+    }
+}
+
+/// 粒子移动、淡出，存活时间结束后销毁
+fn particle_update_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        particle.velocity.y += particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.);
+        sprite.color.set_a(particle.lifetime.percent_left());
+    }
+}
@@ -0,0 +1,446 @@
+use bevy::{
+    prelude::{
+        in_state, not, resource_exists, Color, Commands, Condition, Entity, EventReader, Input,
+        IntoSystemAppConfig, IntoSystemConfig, IntoSystemSetConfig, KeyCode, NextState, OnEnter,
+        OnExit, OnUpdate, Plugin, Query, Res, ResMut, Resource, State, States, SystemSet,
+        Transform, Vec3, With,
+    },
+    text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    time::{Time, Timer, TimerMode},
+    window::WindowFocused,
+};
+
+use crate::{
+    components::DisplayPause,
+    constants::RESUME_COUNTDOWN_SECONDS,
+    resource::{Practice, StaticAssets},
+    state::GameState,
+};
+
+/// 暂停插件
+///
+/// * 暂停是叠加在 `GameState::InGame` 之上的独立状态，暂停和恢复都不会离开 `InGame`
+/// * 暂停时 `Time` 停止，`GameplaySet` 中的系统不再运行，所有实体和资源保持原样
+/// * 练习时可以在暂停状态下单步推进 `GameplaySet`
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_state::<PauseState>()
+            .init_resource::<ResumeCountdown>()
+            .init_resource::<Practice>()
+            .configure_set(
+                GameplaySet.run_if(in_state(PauseState::Running).or_else(frame_step_requested)),
+            )
+            .add_system(clear_frame_step_system.after(GameplaySet))
+            .add_startup_system(listen_visibility_system)
+            .add_system(auto_pause_system.run_if(not(resource_exists::<PauseDisabled>())))
+            .add_system(
+                pause_input_system
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame))
+                    .run_if(not(resource_exists::<PauseDisabled>())),
+            )
+            // 暂停
+            .add_system(enter_paused_system.in_schedule(OnEnter(PauseState::Paused)))
+            .add_system(paused_input_system.in_set(OnUpdate(PauseState::Paused)))
+            .add_system(despawn_overlay_system.in_schedule(OnExit(PauseState::Paused)))
+            // 恢复前的倒计时
+            .add_system(enter_resuming_system.in_schedule(OnEnter(PauseState::Resuming)))
+            .add_system(resuming_countdown_system.in_set(OnUpdate(PauseState::Resuming)))
+            .add_system(despawn_overlay_system.in_schedule(OnExit(PauseState::Resuming)))
+            // 继续运行
+            .add_system(unpause_time_system.in_schedule(OnEnter(PauseState::Running)));
+    }
+}
+
+/// 暂停状态
+#[derive(Debug, Default, States, PartialEq, Eq, Clone, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    /// 恢复前倒计时
+    Resuming,
+}
+
+/// 禁止暂停，回放时时间必须连续
+#[derive(Resource)]
+pub struct PauseDisabled;
+
+/// 游戏逻辑系统集，暂停时不运行
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
+
+/// 恢复前的倒计时，使用真实时间
+#[derive(Resource)]
+pub struct ResumeCountdown(pub Timer);
+
+impl Default for ResumeCountdown {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            RESUME_COUNTDOWN_SECONDS,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// 暂停时请求了单步
+fn frame_step_requested(practice: Res<Practice>) -> bool {
+    practice.frame_step
+}
+
+/// 单步只推进一帧
+fn clear_frame_step_system(mut practice: ResMut<Practice>) {
+    if practice.frame_step {
+        practice.frame_step = false;
+    }
+}
+
+/// 游戏中监听暂停
+fn pause_input_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<PauseState>>) {
+    if kb.just_pressed(KeyCode::P) {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+/// 监听浏览器标签页隐藏
+fn listen_visibility_system() {
+    visibility::listen();
+}
+
+/// 窗口失去焦点或者浏览器标签页被隐藏时自动暂停，恢复前的倒计时中也会回到暂停
+fn auto_pause_system(
+    mut focus_events: EventReader<WindowFocused>,
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    let focus_lost = focus_events.iter().any(|event| !event.focused);
+    // 每一帧都取出标记，避免菜单中隐藏过标签页，开始游戏后立即暂停
+    let hidden = visibility::take_hidden();
+    if (focus_lost || hidden) && state.0 == GameState::InGame && pause_state.0 != PauseState::Paused
+    {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+/// 进入暂停状态，停止时间并显示暂停界面
+fn enter_paused_system(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    static_assets: Res<StaticAssets>,
+) {
+    time.pause();
+
+    // 字体引入
+    let font = static_assets.kenney_future_font.clone();
+    let common_style = TextStyle {
+        font: font.clone(),
+        font_size: 32.,
+        color: Color::BLUE,
+    };
+    let special_style = TextStyle {
+        font: font.clone(),
+        font_size: 38.,
+        color: Color::RED,
+    };
+
+    let align = TextAlignment::Center;
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(vec![
+                TextSection::new("PAUSED  \r\n".to_owned(), common_style.clone()),
+                TextSection::new(" R \r\n".to_owned(), special_style.clone()),
+                TextSection::new("RETURN GAME!".to_owned(), common_style.clone()),
+            ])
+            .with_alignment(align),
+            transform: Transform {
+                translation: Vec3::new(0., 0., 7.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DisplayPause,
+    ));
+}
+
+/// 暂停状态下的键盘监听系统，恢复时先倒计时
+fn paused_input_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<PauseState>>) {
+    if kb.just_pressed(KeyCode::R) {
+        next_state.set(PauseState::Resuming);
+    }
+}
+
+/// 开始恢复前的倒计时
+fn enter_resuming_system(
+    mut commands: Commands,
+    mut countdown: ResMut<ResumeCountdown>,
+    static_assets: Res<StaticAssets>,
+) {
+    countdown.0.reset();
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                countdown_text(&countdown.0),
+                TextStyle {
+                    font: static_assets.kenney_future_font.clone(),
+                    font_size: 96.,
+                    color: Color::RED,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(0., 0., 7.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        DisplayPause,
+    ));
+}
+
+/// 倒计时剩余的整秒数
+fn countdown_text(timer: &Timer) -> String {
+    timer.remaining_secs().ceil().max(1.).to_string()
+}
+
+/// 倒计时，结束后继续游戏
+fn resuming_countdown_system(
+    time: Res<Time>,
+    mut countdown: ResMut<ResumeCountdown>,
+    mut next_state: ResMut<NextState<PauseState>>,
+    mut query: Query<&mut Text, With<DisplayPause>>,
+) {
+    // 游戏时间已经停止，使用真实时间
+    countdown.0.tick(time.raw_delta());
+    if countdown.0.finished() {
+        next_state.set(PauseState::Running);
+        return;
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].value = countdown_text(&countdown.0);
+    }
+}
+
+/// 移除暂停界面
+fn despawn_overlay_system(mut commands: Commands, query: Query<Entity, With<DisplayPause>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 恢复时间
+fn unpause_time_system(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+/// 浏览器标签页的可见性
+#[cfg(target_arch = "wasm32")]
+mod visibility {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use wasm_bindgen::{closure::Closure, JsCast};
+
+    /// 标签页隐藏过，还没有被处理
+    static HIDDEN: AtomicBool = AtomicBool::new(false);
+
+    /// 监听 `visibilitychange` 事件
+    pub fn listen() {
+        let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+            return;
+        };
+        let target = document.clone();
+        let callback = Closure::<dyn FnMut()>::new(move || {
+            if target.hidden() {
+                HIDDEN.store(true, Ordering::Relaxed);
+            }
+        });
+        if document
+            .add_event_listener_with_callback("visibilitychange", callback.as_ref().unchecked_ref())
+            .is_ok()
+        {
+            // 监听在整个运行期间有效
+            callback.forget();
+        }
+    }
+
+    /// 取出并清除隐藏标记
+    pub fn take_hidden() -> bool {
+        HIDDEN.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// 桌面平台只需要处理窗口焦点
+#[cfg(not(target_arch = "wasm32"))]
+mod visibility {
+    pub fn listen() {}
+
+    pub fn take_hidden() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::{
+        components::{DisplayScore, Obstacle, Velocity},
+        resource::GameData,
+        simulation::headless_app,
+    };
+
+    fn test_app() -> App {
+        headless_app(0)
+    }
+
+    /// 跳过菜单和准备状态，直接开始游戏
+    fn start_game(app: &mut App) {
+        app.update();
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Ready);
+        app.update();
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::InGame);
+        for _ in 0..10 {
+            app.update();
+        }
+    }
+
+    /// 按下并松开一个按键
+    fn tap(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+        let mut input = app.world.resource_mut::<Input<KeyCode>>();
+        input.release(key);
+        input.clear();
+    }
+
+    /// 实体的位置和速度
+    type EntityState = (Entity, Vec3, Option<(f32, f32)>);
+
+    /// 除暂停界面外所有实体的状态，以及分数
+    fn snapshot(app: &mut App) -> (Vec<EntityState>, u8) {
+        let mut query = app
+            .world
+            .query_filtered::<(Entity, &Transform, Option<&Velocity>), Without<DisplayPause>>();
+        let mut entities: Vec<_> = query
+            .iter(&app.world)
+            .map(|(entity, transform, velocity)| {
+                (
+                    entity,
+                    transform.translation,
+                    velocity.map(|velocity| (velocity.x, velocity.y)),
+                )
+            })
+            .collect();
+        entities.sort_by_key(|(entity, _, _)| *entity);
+        (entities, app.world.resource::<GameData>().get_score())
+    }
+
+    fn count<T: Component>(app: &mut App) -> usize {
+        app.world
+            .query_filtered::<(), With<T>>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn paused_world_is_frozen() {
+        let mut app = test_app();
+        start_game(&mut app);
+        tap(&mut app, KeyCode::P);
+        let before = snapshot(&mut app);
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.resource::<State<PauseState>>().0,
+            PauseState::Paused
+        );
+        assert_eq!(
+            app.world.resource::<State<GameState>>().0,
+            GameState::InGame
+        );
+        assert!(app.world.resource::<Time>().is_paused());
+        assert_eq!(count::<DisplayPause>(&mut app), 1);
+        assert_eq!(snapshot(&mut app), before);
+    }
+
+    #[test]
+    fn pause_cycle_preserves_world() {
+        let mut app = test_app();
+        start_game(&mut app);
+        let obstacles = count::<Obstacle>(&mut app);
+        assert_eq!(count::<DisplayScore>(&mut app), 1);
+
+        tap(&mut app, KeyCode::P);
+        let before = snapshot(&mut app);
+        app.update();
+        tap(&mut app, KeyCode::R);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(
+            app.world.resource::<State<PauseState>>().0,
+            PauseState::Resuming
+        );
+        assert_eq!(snapshot(&mut app), before);
+
+        // 倒计时结束
+        app.world
+            .resource_mut::<ResumeCountdown>()
+            .0
+            .set_elapsed(Duration::from_secs_f32(RESUME_COUNTDOWN_SECONDS));
+        app.update();
+        assert_eq!(snapshot(&mut app), before);
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<State<PauseState>>().0,
+            PauseState::Running
+        );
+        assert_eq!(
+            app.world.resource::<State<GameState>>().0,
+            GameState::InGame
+        );
+        assert!(!app.world.resource::<Time>().is_paused());
+        assert_eq!(count::<DisplayPause>(&mut app), 0);
+        // 没有重新生成管道和分数显示
+        assert_eq!(count::<Obstacle>(&mut app), obstacles);
+        assert_eq!(count::<DisplayScore>(&mut app), 1);
+        let entities = |(entities, _): (Vec<EntityState>, u8)| {
+            entities
+                .into_iter()
+                .map(|(entity, _, _)| entity)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(entities(snapshot(&mut app)), entities(before));
+    }
+
+    #[test]
+    fn focus_loss_pauses_game() {
+        let mut app = test_app();
+        start_game(&mut app);
+        app.world.send_event(WindowFocused {
+            window: Entity::PLACEHOLDER,
+            focused: false,
+        });
+        app.update();
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<State<PauseState>>().0,
+            PauseState::Paused
+        );
+        assert_eq!(count::<DisplayPause>(&mut app), 1);
+    }
+}
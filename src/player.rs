@@ -1,7 +1,7 @@
 use bevy::{
     prelude::{
-        Audio, Commands, Input, IntoSystemAppConfig, IntoSystemConfigs, KeyCode, OnEnter, OnUpdate,
-        Plugin, Query, Res, ResMut, Transform, Vec3, With,
+        Commands, EventReader, EventWriter, Input, IntoSystemAppConfig, IntoSystemConfigs, KeyCode,
+        Local, OnEnter, OnUpdate, Plugin, Query, Res, ResMut, Transform, Vec3, With,
     },
     sprite::{SpriteSheetBundle, TextureAtlasSprite},
     time::{Timer, TimerMode},
@@ -10,10 +10,12 @@ use bevy::{
 use crate::{
     components::{Movable, Player, PlayerAnimationTimer, Velocity},
     constants::{
-        GRAVITY_VELOCITY, PLAYER_Y_MAX_UP_VELOCITY, PLAYER_Y_MAX_VELOCITY, PLAYER_Y_UP_PIXEL,
-        TIME_STEP,
+        BIRD_BOB_AMPLITUDE, BIRD_BOB_SPEED, GRAVITY_VELOCITY, PLAYER_Y_MAX_UP_VELOCITY,
+        PLAYER_Y_MAX_VELOCITY, PLAYER_Y_UP_PIXEL,
     },
-    resource::{GameData, StaticAssets, WinSize},
+    events::{Flapped, RunStarted},
+    pause::GameplaySet,
+    resource::{GameClock, GameData, StaticAssets, WinSize},
     state::GameState,
 };
 
@@ -22,12 +24,25 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_systems(
-            (input_key_system, bird_automatic_system).in_set(OnUpdate(GameState::InGame)),
+            (input_key_system, bird_automatic_system)
+                .in_set(GameplaySet)
+                .in_set(OnUpdate(GameState::InGame)),
         )
-        .add_system(spawn_bird_system.in_schedule(OnEnter(GameState::InGame)));
+        // 准备状态下小鸟悬停浮动，第一次飞行后才开始游戏
+        .add_systems(
+            (bird_bob_system, input_key_system)
+                .chain()
+                .in_set(OnUpdate(GameState::Ready)),
+        )
+        .add_system(spawn_bird_system.in_schedule(OnEnter(GameState::Ready)));
     }
 }
 
+/// 小鸟的初始位置
+fn bird_start_position(win_size: &WinSize) -> (f32, f32) {
+    (-win_size.width / 4. / 2., win_size.height / 2. / 3.)
+}
+
 /// 产生玩家
 fn spawn_bird_system(
     mut commands: Commands,
@@ -37,7 +52,7 @@ fn spawn_bird_system(
 ) {
     if !game_data.player_alive() {
         let bird = static_assets.player.clone();
-        let (x, y) = (-win_size.width / 4. / 2., win_size.height / 2. / 3.);
+        let (x, y) = bird_start_position(&win_size);
         commands.spawn((
             SpriteSheetBundle {
                 texture_atlas: bird,
@@ -62,28 +77,51 @@ fn spawn_bird_system(
 /// 游戏中键盘事件系统
 fn input_key_system(
     kb: Res<Input<KeyCode>>,
-    static_assets: Res<StaticAssets>,
-    audio_player: Res<Audio>,
+    mut flapped_events: EventWriter<Flapped>,
     mut query: Query<(&mut Velocity, &mut Transform), With<Player>>,
 ) {
     if kb.just_released(KeyCode::Space) {
         let vt = query.get_single_mut();
         // 松开空格后，直接向上20像素，并且给一个向上的速度。
-        match vt {
-            Ok((mut velocity, mut transform)) => {
-                transform.translation.y += PLAYER_Y_UP_PIXEL;
-                velocity.y = PLAYER_Y_MAX_UP_VELOCITY;
-            }
-            _ => (),
+        if let Ok((mut velocity, mut transform)) = vt {
+            transform.translation.y += PLAYER_Y_UP_PIXEL;
+            velocity.y = PLAYER_Y_MAX_UP_VELOCITY;
+            flapped_events.send(Flapped {
+                position: transform.translation,
+            });
         }
-        audio_player.play(static_assets.fly_audio.clone());
+    }
+}
+
+/// 准备状态下小鸟在初始位置上下浮动
+///
+/// * 从每一局开始时计时，按 `GameClock` 推进，第一次飞行时的位置只和帧数有关
+fn bird_bob_system(
+    mut started_events: EventReader<RunStarted>,
+    clock: Res<GameClock>,
+    win_size: Res<WinSize>,
+    mut elapsed: Local<f32>,
+    mut query: Query<&mut Transform, With<Player>>,
+) {
+    if !started_events.is_empty() {
+        started_events.clear();
+        *elapsed = 0.;
+    }
+    let (_, y) = bird_start_position(&win_size);
+    let offset = (*elapsed * BIRD_BOB_SPEED).sin() * BIRD_BOB_AMPLITUDE;
+    *elapsed += clock.step();
+    for mut transform in query.iter_mut() {
+        transform.translation.y = y + offset;
     }
 }
 
 /// 小鸟重力系统
-fn bird_automatic_system(mut query: Query<&mut Velocity, (With<Player>, With<Movable>)>) {
+fn bird_automatic_system(
+    clock: Res<GameClock>,
+    mut query: Query<&mut Velocity, (With<Player>, With<Movable>)>,
+) {
     for mut velocity in query.iter_mut() {
-        velocity.y = velocity.y - GRAVITY_VELOCITY * TIME_STEP;
+        velocity.y -= GRAVITY_VELOCITY * clock.step();
         if velocity.y < -PLAYER_Y_MAX_VELOCITY {
             velocity.y = -PLAYER_Y_MAX_VELOCITY;
         }
@@ -0,0 +1,207 @@
+use bevy::{
+    log::info,
+    prelude::{
+        Color, Commands, DetectChanges, Input, IntoSystemConfig, KeyCode, OnUpdate, Plugin, Query,
+        Res, ResMut, StartupSet, State, Transform, Vec3, Visibility, With,
+    },
+    sprite::Anchor,
+    text::{Text, Text2dBundle, TextStyle},
+    time::Time,
+};
+
+use crate::{
+    components::DisplayPractice,
+    constants::{
+        GROUND_IMG_SIZE, PIPE_IMG_SIZE, PRACTICE_GAPS, PRACTICE_SCORE_STEP, PRACTICE_TIME_SCALES,
+    },
+    events::RunStats,
+    obstacle::{spawn_pipe_pair, PipeGap},
+    pause::{GameplaySet, PauseState},
+    resource::{GameClock, GameData, Practice, StaticAssets, WinSize},
+    state::GameState,
+};
+
+/// 练习插件
+///
+/// * I 无敌：撞击只记录不处理
+/// * [ 和 ] 调整时间缩放（慢动作）
+/// * 暂停时按 . 推进一帧
+/// * T 得分跳到下一个 10 分，测试后期的难度
+/// * 1-4 在画面右侧生成指定大小的可通过区域
+/// * 使用过练习工具的一局不计入记录和成就
+/// * 只在 `--practice` 启动时添加，正常游戏和回放中这些按键不起作用
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(spawn_practice_display_system.in_base_set(StartupSet::PostStartup))
+            .add_system(practice_toggle_system)
+            .add_system(
+                frame_step_system
+                    .before(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            .add_system(teleport_score_system.in_set(OnUpdate(GameState::InGame)))
+            .add_system(spawn_gap_system.in_set(OnUpdate(GameState::InGame)))
+            .add_system(mark_practice_system.in_set(OnUpdate(GameState::InGame)))
+            .add_system(practice_display_system);
+    }
+}
+
+/// 切换无敌和时间缩放
+fn practice_toggle_system(
+    kb: Res<Input<KeyCode>>,
+    mut practice: ResMut<Practice>,
+    mut clock: ResMut<GameClock>,
+    mut time: ResMut<Time>,
+) {
+    if kb.just_pressed(KeyCode::I) {
+        practice.invincible = !practice.invincible;
+        info!("无敌模式 {}", practice.invincible);
+    }
+
+    let index = PRACTICE_TIME_SCALES
+        .iter()
+        .position(|&scale| scale == clock.time_scale)
+        .unwrap_or(PRACTICE_TIME_SCALES.len() - 1);
+    let index = if kb.just_pressed(KeyCode::LBracket) {
+        index.saturating_sub(1)
+    } else if kb.just_pressed(KeyCode::RBracket) {
+        (index + 1).min(PRACTICE_TIME_SCALES.len() - 1)
+    } else {
+        return;
+    };
+    clock.time_scale = PRACTICE_TIME_SCALES[index];
+    // 计时器（动画、震动等）也一起放慢
+    time.set_relative_speed(clock.time_scale);
+    info!("时间缩放 {}", clock.time_scale);
+}
+
+/// 暂停时推进一帧
+fn frame_step_system(
+    kb: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut practice: ResMut<Practice>,
+    mut stats: ResMut<RunStats>,
+) {
+    if kb.just_pressed(KeyCode::Period) && pause_state.0 == PauseState::Paused {
+        practice.frame_step = true;
+        stats.practice = true;
+    }
+}
+
+/// 得分跳到下一个 10 分，最多跳到 `u8::MAX` 以下最大的整 10 分，之后还能继续得分
+fn teleport_score_system(
+    kb: Res<Input<KeyCode>>,
+    mut game_data: ResMut<GameData>,
+    mut stats: ResMut<RunStats>,
+) {
+    if !kb.just_pressed(KeyCode::T) {
+        return;
+    }
+    let score = game_data.get_score();
+    let max = (u8::MAX - 1) / PRACTICE_SCORE_STEP * PRACTICE_SCORE_STEP;
+    let score = (score / PRACTICE_SCORE_STEP)
+        .saturating_add(1)
+        .saturating_mul(PRACTICE_SCORE_STEP)
+        .min(max);
+    game_data.set_score(score);
+    stats.score = score as u32;
+    stats.practice = true;
+    info!("得分跳到 {}", score);
+}
+
+/// 在画面右侧生成指定大小的可通过区域，位于可以飞行的区域中间
+fn spawn_gap_system(
+    mut commands: Commands,
+    kb: Res<Input<KeyCode>>,
+    static_assets: Res<StaticAssets>,
+    win_size: Res<WinSize>,
+    mut stats: ResMut<RunStats>,
+) {
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    let Some(size) = keys
+        .iter()
+        .zip(PRACTICE_GAPS)
+        .find(|(key, _)| kb.just_pressed(**key))
+        .map(|(_, size)| size)
+    else {
+        return;
+    };
+    let top = win_size.height / 2.;
+    let ground = -(win_size.height / 2. - GROUND_IMG_SIZE.1);
+    let center = (top + ground) / 2.;
+    let gap = PipeGap {
+        bottom: center - size / 2.,
+        top: center + size / 2.,
+    };
+    let x = win_size.width / 2. + PIPE_IMG_SIZE.0 / 2.;
+    spawn_pipe_pair(&mut commands, &static_assets, x, gap);
+    stats.practice = true;
+    info!("生成可通过区域 {}", size);
+}
+
+/// 开着无敌或者慢动作时，这一局算作练习
+fn mark_practice_system(
+    practice: Res<Practice>,
+    clock: Res<GameClock>,
+    mut stats: ResMut<RunStats>,
+) {
+    if (practice.invincible || clock.time_scale != 1.) && !stats.practice {
+        stats.practice = true;
+    }
+}
+
+/// 生成练习工具状态显示
+fn spawn_practice_display_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    static_assets: Res<StaticAssets>,
+) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: static_assets.kenney_future_font.clone(),
+                    font_size: 16.,
+                    color: Color::ORANGE,
+                },
+            ),
+            text_anchor: Anchor::BottomLeft,
+            transform: Transform {
+                translation: Vec3::new(-win_size.width / 2. + 8., -win_size.height / 2. + 8., 10.),
+                ..Default::default()
+            },
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+        DisplayPractice,
+    ));
+}
+
+/// 显示正在使用的练习工具
+fn practice_display_system(
+    practice: Res<Practice>,
+    clock: Res<GameClock>,
+    mut query: Query<(&mut Text, &mut Visibility), With<DisplayPractice>>,
+) {
+    if !practice.is_changed() && !clock.is_changed() {
+        return;
+    }
+    let mut tools = vec![];
+    if practice.invincible {
+        tools.push("INVINCIBLE".to_owned());
+    }
+    if clock.time_scale != 1. {
+        tools.push(format!("SPEED x{}", clock.time_scale));
+    }
+    for (mut text, mut visibility) in query.iter_mut() {
+        text.sections[0].value = format!("PRACTICE  {}", tools.join("  "));
+        *visibility = if tools.is_empty() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    core::FrameCount,
+    input::InputSystem,
+    log::{info, warn},
+    prelude::{
+        resource_exists, CoreSet, EventReader, EventWriter, Input, IntoSystemConfig, KeyCode,
+        Plugin, Res, ResMut, Resource, State,
+    },
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::START_FLAPS,
+    events::{RunRecorded, RunStarted},
+    pause::PauseState,
+    resource::{Difficulty, GameRng, Settings},
+    simulation::Simulation,
+    state::GameState,
+};
+
+/// 回放插件
+///
+/// * 存在 `ReplayPlayback` 时，在指定的帧模拟按下空格
+/// * 录制每一局的种子和输入，结束时发送 `RunRecorded`，指定了路径时把第一局保存为回放文件
+/// * 回放需要配合 `FixedStepPlugin`，否则帧数和时间对应不上
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(
+            playback_system
+                .in_base_set(CoreSet::PreUpdate)
+                .after(InputSystem)
+                .run_if(resource_exists::<ReplayPlayback>()),
+        )
+        .init_resource::<ReplayRecorder>()
+        .add_system(record_system)
+        .add_system(save_recording_system.after(record_system));
+    }
+}
+
+/// 一局游戏的完整输入
+///
+/// * 使用相同的种子和输入，模拟的结果完全相同
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    /// 管道随机数的种子
+    pub seed: u64,
+    /// 难度
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    /// 按下空格的帧数，从程序启动时的第 0 帧开始，按从小到大排列
+    pub flaps: Vec<u32>,
+}
+
+impl Replay {
+    /// 读取回放文件
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("读取 {} 失败: {}", path.display(), err))?;
+        ron::from_str(&content).map_err(|err| format!("{} 格式错误: {}", path.display(), err))
+    }
+
+    /// 保存回放文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let config = PrettyConfig::default().compact_arrays(true);
+        let content = ron::ser::to_string_pretty(self, config)
+            .map_err(|err| format!("序列化回放失败: {}", err))?;
+        fs::write(path, content + "\n")
+            .map_err(|err| format!("写入 {} 失败: {}", path.display(), err))
+    }
+}
+
+/// 回放的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    /// 本局得分
+    pub score: u32,
+    /// 撞击时的帧数，在最大帧数内没有撞击时为 `None`
+    pub death_frame: Option<u32>,
+    /// 每一帧小鸟位置的哈希，轨迹有任何变化都会改变
+    pub trajectory_hash: u64,
+}
+
+/// 无界面地模拟一局回放，直到游戏结束或者超过 `max_frames` 帧
+pub fn play(replay: &Replay, max_frames: u32) -> ReplayResult {
+    let mut sim = Simulation::new(replay.seed);
+    sim.app().world.resource_mut::<Settings>().difficulty = replay.difficulty;
+    let mut flaps = replay.flaps.iter().peekable();
+    let mut hash = TrajectoryHash::default();
+    let mut death_frame = None;
+
+    while sim.frame() < max_frames && sim.state() != GameState::GameOver {
+        if flaps.next_if(|&&frame| frame <= sim.frame()).is_some() {
+            sim.flap();
+        } else {
+            sim.step();
+        }
+        if let Some(bird) = sim.bird() {
+            hash.write(bird.x);
+            hash.write(bird.y);
+        }
+        if death_frame.is_none() && sim.state() == GameState::Dying {
+            death_frame = Some(sim.frame());
+        }
+    }
+
+    ReplayResult {
+        score: sim.run_stats().score,
+        death_frame,
+        trajectory_hash: hash.0,
+    }
+}
+
+/// 声明的一局结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+    pub score: u32,
+    /// 撞击时的帧数
+    pub death_frame: u32,
+}
+
+/// 验证回放的结论
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Verdict {
+    pub valid: bool,
+    pub claimed: Claim,
+    /// 重新模拟得到的结果，输入格式错误时为 `None`
+    pub actual: Option<ReplayResult>,
+    /// 无效的原因
+    pub reason: Option<String>,
+}
+
+/// 重新模拟回放，检查声明的得分和撞击帧数是否一致
+///
+/// * 服务器用它拒绝伪造的成绩，输入必须按帧数从小到大排列
+/// * 在 `max_frames` 帧内没有撞击的回放无效
+pub fn verify(replay: &Replay, claimed: Claim, max_frames: u32) -> Verdict {
+    let invalid = |claimed, actual, reason| Verdict {
+        valid: false,
+        claimed,
+        actual,
+        reason: Some(reason),
+    };
+    if replay.flaps.windows(2).any(|pair| pair[0] > pair[1]) {
+        return invalid(claimed, None, "输入没有按帧数排列".to_owned());
+    }
+
+    let actual = play(replay, max_frames);
+    if actual.score != claimed.score {
+        let reason = format!("得分不一致: 声明 {}，实际 {}", claimed.score, actual.score);
+        return invalid(claimed, Some(actual), reason);
+    }
+    if actual.death_frame != Some(claimed.death_frame) {
+        let reason = match actual.death_frame {
+            Some(frame) => format!(
+                "撞击帧数不一致: 声明 {}，实际 {}",
+                claimed.death_frame, frame
+            ),
+            None => format!(
+                "撞击帧数不一致: 声明 {}，实际在 {} 帧内没有撞击",
+                claimed.death_frame, max_frames
+            ),
+        };
+        return invalid(claimed, Some(actual), reason);
+    }
+    Verdict {
+        valid: true,
+        claimed,
+        actual: Some(actual),
+        reason: None,
+    }
+}
+
+/// 等待模拟按下空格的帧数
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    flaps: VecDeque<u32>,
+}
+
+impl ReplayPlayback {
+    pub fn new(flaps: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            flaps: flaps.into_iter().collect(),
+        }
+    }
+}
+
+/// 录制每一局的输入
+///
+/// * 帧数换算成从启动开始单独模拟这一局时的帧数：第 `START_FLAPS[0]` 帧离开菜单，下一帧开始这一局，
+///   之后只计算没有暂停的帧，所以之后的每一局和暂停过的一局都可以单独回放
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    /// 这一局按下空格的帧数，没有在录制时为 `None`
+    flaps: Option<Vec<u32>>,
+    /// 当前的帧数
+    frame: u32,
+    /// 撞击时的帧数
+    death_frame: Option<u32>,
+    /// 第一局的回放保存到这个文件
+    path: Option<PathBuf>,
+    saved: bool,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            ..Default::default()
+        }
+    }
+}
+
+/// 到达指定的帧时按下并松开空格，和 `Simulation::flap` 一样每帧最多一次
+fn playback_system(
+    frame: Res<FrameCount>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut kb: ResMut<Input<KeyCode>>,
+) {
+    if playback.flaps.front().is_some_and(|&flap| flap <= frame.0) {
+        playback.flaps.pop_front();
+        kb.press(KeyCode::Space);
+        kb.release(KeyCode::Space);
+    }
+}
+
+/// 记录松开空格的帧数，进入 `GameOver` 时结束这一局的录制
+#[allow(clippy::too_many_arguments)]
+fn record_system(
+    mut started_events: EventReader<RunStarted>,
+    kb: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    rng: Res<GameRng>,
+    settings: Res<Settings>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut recorded_events: EventWriter<RunRecorded>,
+) {
+    if !started_events.is_empty() {
+        started_events.clear();
+        // 单独回放时先按一次空格离开菜单
+        recorder.flaps = Some(vec![START_FLAPS[0]]);
+        recorder.frame = START_FLAPS[0] + 1;
+        recorder.death_frame = None;
+    } else if pause_state.0 == PauseState::Running {
+        recorder.frame += 1;
+    }
+    let recorder = recorder.as_mut();
+    let Some(flaps) = &mut recorder.flaps else {
+        return;
+    };
+
+    match state.0 {
+        GameState::Dying if recorder.death_frame.is_none() => {
+            // `play` 推进完这一帧之后才检查状态
+            recorder.death_frame = Some(recorder.frame + 1);
+        }
+        GameState::GameOver => {
+            if let Some(death_frame) = recorder.death_frame {
+                recorded_events.send(RunRecorded {
+                    replay: Replay {
+                        seed: rng.seed,
+                        difficulty: settings.difficulty,
+                        flaps: std::mem::take(flaps),
+                    },
+                    death_frame,
+                });
+            }
+            recorder.flaps = None;
+        }
+        _ if kb.just_released(KeyCode::Space) && pause_state.0 == PauseState::Running => {
+            flaps.push(recorder.frame);
+        }
+        _ => {}
+    }
+}
+
+/// 第一局录制完成时保存回放
+fn save_recording_system(
+    mut recorded_events: EventReader<RunRecorded>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let Some(event) = recorded_events.iter().next() else {
+        return;
+    };
+    let Some(path) = recorder.path.as_ref().filter(|_| !recorder.saved) else {
+        return;
+    };
+    match event.replay.save(path) {
+        Ok(()) => info!("回放已保存到 {}", path.display()),
+        Err(err) => warn!("{}", err),
+    }
+    recorder.saved = true;
+}
+
+/// FNV-1a 哈希，不依赖标准库哈希的实现，不同平台和版本结果相同
+struct TrajectoryHash(u64);
+
+impl Default for TrajectoryHash {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl TrajectoryHash {
+    fn write(&mut self, value: f32) {
+        for byte in value.to_bits().to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
@@ -1,11 +1,18 @@
 use bevy::{
-    prelude::{AudioSource, Handle, Image, Resource},
+    prelude::{AudioSource, Color, Handle, Image, Resource},
     sprite::TextureAtlas,
     text::Font,
+    time::{Timer, TimerMode},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    MEDAL_SCORES, SPAWN_OBSTACLE_CHECK_SECONDS, TIME_STEP, VOLUME_STEP, WINDOW_SCALES,
 };
 
 /// 游戏数据资源
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GameData {
     score: u8,
     alive: bool,
@@ -39,14 +46,21 @@ impl GameData {
     pub fn death(&mut self) {
         self.alive = false;
         self.score = 0;
+        self.need_add_obstacle = false;
     }
 
     pub fn get_score(&self) -> u8 {
         self.score
     }
 
+    /// 直接设置得分，练习时使用
+    pub fn set_score(&mut self, score: u8) {
+        self.score = score;
+    }
+
+    /// 得分加一，达到 `u8::MAX` 后不再增加
     pub fn add_score(&mut self) {
-        self.score += 1;
+        self.score = self.score.saturating_add(1);
     }
 
     pub fn player_alive(&self) -> bool {
@@ -55,22 +69,119 @@ impl GameData {
 }
 
 /// 窗口大小资源
+///
+/// * `width`、`height` 是游戏区域的逻辑大小，不随窗口变化，生成和碰撞的边界都以它为准
+/// * `scale` 是每个逻辑单位对应的窗口像素，窗口大小变化时更新
 #[derive(Resource)]
 pub struct WinSize {
     pub width: f32,
     pub height: f32,
+    pub scale: f32,
 }
 
-/// 静态资源
+/// 游戏逻辑的速度
+///
+/// * 移动、重力等每帧按 `step()` 秒推进，练习时可以放慢
+#[derive(Resource)]
+pub struct GameClock {
+    /// 时间缩放，1 为正常速度
+    pub time_scale: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self { time_scale: 1. }
+    }
+}
+
+impl GameClock {
+    /// 这一帧推进的时间（秒）
+    pub fn step(&self) -> f32 {
+        TIME_STEP * self.time_scale
+    }
+}
+
+/// 检查是否需要生成管道的计时器
+///
+/// * 按 `GameClock` 推进，每一局进入游戏时重新计时，管道的位置只和帧数有关
 #[derive(Resource)]
+pub struct ObstacleSpawnTimer(pub Timer);
+
+impl Default for ObstacleSpawnTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            SPAWN_OBSTACLE_CHECK_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// 练习工具
+#[derive(Resource, Default)]
+pub struct Practice {
+    /// 无敌，撞击只记录不处理
+    pub invincible: bool,
+    /// 暂停时推进一帧
+    pub frame_step: bool,
+}
+
+/// 调试信息是否显示
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+/// 影响玩法的随机数（管道的位置和间隙）
+///
+/// * 使用相同的种子可以得到完全相同的管道，模拟和测试依赖这一点
+/// * 默认使用随机的种子，每一局开始时更换种子，种子会记录在每一局的统计中
+#[derive(Resource)]
+pub struct GameRng {
+    pub rng: StdRng,
+    /// 当前这一局的种子
+    pub seed: u64,
+    /// 生成之后每一局的种子，和管道使用的随机数分开，不受上一局的影响
+    seeds: StdRng,
+    /// 是否已经开始过一局
+    started: bool,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            seeds: StdRng::seed_from_u64(seed),
+            started: false,
+        }
+    }
+
+    /// 开始新的一局
+    ///
+    /// * 第一局使用启动时的种子，之后每一局取一个新的种子重新初始化，每一局都可以单独回放
+    pub fn start_run(&mut self) {
+        if self.started {
+            self.seed = self.seeds.gen();
+            self.rng = StdRng::seed_from_u64(self.seed);
+        }
+        self.started = true;
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::random())
+    }
+}
+
+/// 静态资源
+#[derive(Resource, Default)]
 pub struct StaticAssets {
     /* 图片 */
     /// 玩家动画
     pub player: Handle<TextureAtlas>,
     /// 管道图片
     pub pipe: Handle<Image>,
-    /// 背景图片
-    pub background: Handle<Image>,
     /// 地面图片
     pub ground: Handle<Image>,
 
@@ -88,3 +199,197 @@ pub struct StaticAssets {
     /// 游戏字体
     pub kenney_future_font: Handle<Font>,
 }
+
+/// 游戏难度
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// 可通过区域的缩放比例
+    pub fn gap_scale(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.75,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Hard,
+            Difficulty::Normal => Difficulty::Easy,
+            Difficulty::Hard => Difficulty::Normal,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+}
+
+/// 游戏区域缩放到窗口的方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScaleMode {
+    /// 按比例缩放，多余部分显示黑边
+    #[default]
+    Letterbox,
+    /// 只按整数倍缩放，保证像素清晰
+    Integer,
+}
+
+impl ScaleMode {
+    pub fn toggle(&self) -> Self {
+        match self {
+            ScaleMode::Letterbox => ScaleMode::Integer,
+            ScaleMode::Integer => ScaleMode::Letterbox,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScaleMode::Letterbox => "LETTERBOX",
+            ScaleMode::Integer => "INTEGER",
+        }
+    }
+}
+
+/// 游戏设置资源，修改后立即生效并保存到本地
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 主音量 0 ~ 1
+    pub master_volume: f32,
+    /// 音效音量 0 ~ 1
+    pub sfx_volume: f32,
+    /// 音乐音量 0 ~ 1
+    pub music_volume: f32,
+    /// 是否静音
+    pub muted: bool,
+    /// 是否全屏
+    pub fullscreen: bool,
+    /// 是否开启垂直同步
+    pub vsync: bool,
+    /// 窗口缩放倍数
+    pub window_scale: f32,
+    /// 游戏区域缩放方式
+    pub scale_mode: ScaleMode,
+    /// 游戏难度
+    pub difficulty: Difficulty,
+    /// 是否显示帧率
+    pub show_fps: bool,
+    /// 是否显示粒子特效
+    pub effects: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            sfx_volume: 1.,
+            music_volume: 0.6,
+            muted: false,
+            fullscreen: false,
+            vsync: true,
+            window_scale: WINDOW_SCALES[0],
+            scale_mode: ScaleMode::Letterbox,
+            difficulty: Difficulty::Normal,
+            show_fps: false,
+            effects: true,
+        }
+    }
+}
+
+impl Settings {
+    /// 按步长调整音量，结果限制在 0 ~ 1 之间
+    pub fn step_volume(volume: f32, direction: f32) -> f32 {
+        let steps = (volume / VOLUME_STEP).round() + direction;
+        (steps * VOLUME_STEP).clamp(0., 1.)
+    }
+
+    /// 在可选的窗口缩放倍数中切换
+    pub fn step_window_scale(&mut self, direction: isize) {
+        let len = WINDOW_SCALES.len() as isize;
+        let current = WINDOW_SCALES
+            .iter()
+            .position(|scale| *scale == self.window_scale)
+            .unwrap_or(0) as isize;
+        let next = (current + direction).rem_euclid(len) as usize;
+        self.window_scale = WINDOW_SCALES[next];
+    }
+}
+
+/// 本地保存的记录
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Records {
+    /// 最高分
+    pub best: u8,
+}
+
+impl Records {
+    /// 提交一局的分数，打破记录时返回 true
+    pub fn submit(&mut self, score: u8) -> bool {
+        if score > self.best {
+            self.best = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// 奖牌
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl Medal {
+    const ALL: [Medal; 4] = [Medal::Bronze, Medal::Silver, Medal::Gold, Medal::Platinum];
+
+    /// 分数对应的奖牌，分数不够时没有奖牌
+    pub fn from_score(score: u8) -> Option<Medal> {
+        MEDAL_SCORES
+            .iter()
+            .rposition(|min| score >= *min)
+            .map(|index| Medal::ALL[index])
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Medal::Bronze => "BRONZE",
+            Medal::Silver => "SILVER",
+            Medal::Gold => "GOLD",
+            Medal::Platinum => "PLATINUM",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Medal::Bronze => Color::rgb(0.8, 0.5, 0.2),
+            Medal::Silver => Color::rgb(0.75, 0.75, 0.8),
+            Medal::Gold => Color::rgb(1., 0.84, 0.),
+            Medal::Platinum => Color::rgb(0.9, 0.95, 1.),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    log::warn,
+    prelude::{
+        EventReader, IntoSystemAppConfig, IntoSystemConfig, OnEnter, Plugin, Res, ResMut, Resource,
+        Vec3,
+    },
+    time::Time,
+};
+use serde::Serialize;
+
+use crate::{
+    events::{BirdCrashed, CrashCause, PipePassed, RunEnded, RunStarted},
+    resource::{Difficulty, GameRng, Settings},
+    state::GameState,
+};
+
+/// 每局统计插件
+///
+/// * 只在用 `--run-log` 指定文件时记录，文件由使用者自己管理
+/// * 每一局结束时把种子、得分、时长、每组管道的通过距离和撞击信息追加到本地文件
+/// * 扩展名为 `.csv` 时写入 CSV，否则每行写入一个 JSON
+pub struct RunLogPlugin;
+
+impl Plugin for RunLogPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<RunLog>()
+            .init_resource::<RunTracker>()
+            .add_system(start_tracking_system.in_schedule(OnEnter(GameState::InGame)))
+            .add_system(track_run_system)
+            .add_system(write_run_system.after(track_run_system));
+    }
+}
+
+/// 每局统计文件的路径，由 `--run-log` 指定，为 `None` 时不写入
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RunLog {
+    pub path: Option<PathBuf>,
+}
+
+/// 本局中 `RunStats` 以外需要记录的数据
+#[derive(Resource, Default)]
+struct RunTracker {
+    /// 进入游戏时的时间
+    start: f32,
+    /// 撞击时已经进行的时间
+    duration: f32,
+    clearances: Vec<f32>,
+    crash: Option<Vec3>,
+}
+
+/// 一局的统计记录
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunRecord {
+    pub version: &'static str,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub score: u32,
+    /// 从进入游戏到撞击的秒数，不包括暂停的时间
+    pub duration: f32,
+    pub flaps: u32,
+    /// 通过每组管道时与管道的最小距离
+    pub clearances: Vec<f32>,
+    pub cause: Option<CrashCause>,
+    pub death_x: Option<f32>,
+    pub death_y: Option<f32>,
+    /// 使用过练习工具
+    pub practice: bool,
+}
+
+impl RunRecord {
+    const CSV_HEADER: &str =
+        "version,seed,difficulty,score,duration,flaps,clearances,cause,death_x,death_y,practice";
+
+    /// CSV 中的一行，通过距离之间用 `;` 分隔
+    fn to_csv(&self) -> String {
+        let optional = |value: Option<f32>| value.map(|value| value.to_string());
+        let clearances: Vec<_> = self.clearances.iter().map(f32::to_string).collect();
+        let cause = match self.cause {
+            Some(CrashCause::Ceiling) => "ceiling",
+            Some(CrashCause::Ground) => "ground",
+            Some(CrashCause::Pipe) => "pipe",
+            None => "",
+        };
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.version,
+            self.seed,
+            self.difficulty.label().to_lowercase(),
+            self.score,
+            self.duration,
+            self.flaps,
+            clearances.join(";"),
+            cause,
+            optional(self.death_x).unwrap_or_default(),
+            optional(self.death_y).unwrap_or_default(),
+            self.practice
+        )
+    }
+
+    /// 追加到文件末尾，CSV 文件为空时先写入表头
+    pub fn append(&self, path: &Path) -> Result<(), String> {
+        let csv = path.extension().is_some_and(|ext| ext == "csv");
+        let mut content = if csv {
+            self.to_csv()
+        } else {
+            serde_json::to_string(self).map_err(|err| err.to_string())?
+        };
+        content.push('\n');
+
+        let error = |err: std::io::Error| format!("写入 {} 失败: {}", path.display(), err);
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(error)?;
+        if csv && file.metadata().map_err(error)?.len() == 0 {
+            content = format!("{}\n{}", RunRecord::CSV_HEADER, content);
+        }
+        file.write_all(content.as_bytes()).map_err(error)
+    }
+}
+
+/// 进入游戏时开始计时
+fn start_tracking_system(time: Res<Time>, mut tracker: ResMut<RunTracker>) {
+    tracker.start = time.elapsed_seconds();
+}
+
+/// 记录每组管道的通过距离
+fn track_run_system(
+    mut started_events: EventReader<RunStarted>,
+    mut passed_events: EventReader<PipePassed>,
+    mut tracker: ResMut<RunTracker>,
+) {
+    if !started_events.is_empty() {
+        started_events.clear();
+        *tracker = RunTracker::default();
+    }
+    for event in passed_events.iter() {
+        tracker.clearances.push(event.clearance);
+    }
+}
+
+/// 记录撞击的位置和时间，本局结束时写入文件
+///
+/// * `RunEnded` 在 `BirdCrashed` 之后发送，同一个系统读取两个事件才能保证先记录撞击
+fn write_run_system(
+    mut crashed_events: EventReader<BirdCrashed>,
+    mut ended_events: EventReader<RunEnded>,
+    time: Res<Time>,
+    rng: Res<GameRng>,
+    settings: Res<Settings>,
+    run_log: Res<RunLog>,
+    mut tracker: ResMut<RunTracker>,
+) {
+    if let Some(event) = crashed_events.iter().last() {
+        tracker.crash = Some(event.position);
+        tracker.duration = time.elapsed_seconds() - tracker.start;
+    }
+    let Some(event) = ended_events.iter().last() else {
+        return;
+    };
+    let Some(path) = &run_log.path else {
+        return;
+    };
+
+    let stats = &event.stats;
+    let record = RunRecord {
+        version: env!("CARGO_PKG_VERSION"),
+        seed: rng.seed,
+        difficulty: settings.difficulty,
+        score: stats.score,
+        duration: tracker.duration,
+        flaps: stats.flaps,
+        clearances: tracker.clearances.clone(),
+        cause: stats.cause,
+        death_x: tracker.crash.map(|position| position.x),
+        death_y: tracker.crash.map(|position| position.y),
+        practice: stats.practice,
+    };
+    if let Err(err) = record.append(path) {
+        warn!("{}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bot::BotPolicy, simulation::Simulation};
+
+    fn play_logged_run(path: &Path) {
+        let mut sim = Simulation::new(1);
+        sim.app().add_plugin(RunLogPlugin).insert_resource(RunLog {
+            path: Some(path.to_owned()),
+        });
+        sim.start_run();
+        sim.run_until(3000, |sim| {
+            BotPolicy::Hover.step(sim);
+            sim.state() == GameState::GameOver
+        })
+        .expect("没有在 3000 帧内结束");
+    }
+
+    #[test]
+    fn appends_one_record_per_run() {
+        let dir = std::env::temp_dir().join(format!("flappy_run_log_{}", std::process::id()));
+        let (jsonl, csv) = (dir.join("runs.jsonl"), dir.join("runs.csv"));
+        play_logged_run(&jsonl);
+        play_logged_run(&jsonl);
+        play_logged_run(&csv);
+
+        let content = fs::read_to_string(&jsonl).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], lines[1]);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["seed"], 1);
+        assert_eq!(record["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            record["clearances"].as_array().unwrap().len() as u64,
+            record["score"].as_u64().unwrap()
+        );
+        assert!(record["duration"].as_f64().unwrap() > 0.);
+        assert!(record["death_y"].is_number());
+
+        let content = fs::read_to_string(&csv).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], RunRecord::CSV_HEADER);
+        assert!(lines[1].starts_with(&format!("{},1,normal,", env!("CARGO_PKG_VERSION"))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn each_run_logs_its_own_seed() {
+        let dir = std::env::temp_dir().join(format!("flappy_run_seed_{}", std::process::id()));
+        let path = dir.join("runs.jsonl");
+        let mut sim = Simulation::new(1);
+        sim.app().add_plugin(RunLogPlugin).insert_resource(RunLog {
+            path: Some(path.clone()),
+        });
+        sim.start_run();
+        sim.run_until(3000, |sim| sim.state() == GameState::GameOver)
+            .expect("没有在 3000 帧内结束");
+        // 结算面板滑入后按空格重新开始
+        sim.run_until(600, |sim| {
+            sim.flap();
+            sim.state() != GameState::GameOver
+        })
+        .expect("没有重新开始");
+        sim.run_until(3000, |sim| sim.state() == GameState::GameOver)
+            .expect("没有在 3000 帧内结束");
+
+        let content = fs::read_to_string(&path).unwrap();
+        let seeds: Vec<u64> = content
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["seed"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+        // 第一局使用启动时的种子，第二局换成新的种子
+        let mut rng = GameRng::from_seed(1);
+        rng.start_run();
+        rng.start_run();
+        assert_ne!(rng.seed, 1);
+        assert_eq!(seeds, [1, rng.seed]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,364 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::{
+        Color, Commands, DetectChanges, Entity, Input, IntoSystemAppConfig, IntoSystemConfig,
+        IntoSystemConfigs, KeyCode, NextState, OnEnter, OnExit, OnUpdate, Plugin, Query, Ref, Res,
+        ResMut, StartupSet, Transform, Vec3, Visibility, With,
+    },
+    sprite::Anchor,
+    text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    window::{PresentMode, Window, WindowMode},
+};
+
+use crate::{
+    cli::LaunchOptions,
+    components::{DisplayFps, DisplaySettings},
+    constants::SETTINGS_FILE_NAME,
+    resource::{Settings, StaticAssets, WinSize},
+    state::GameState,
+    storage::Storage,
+};
+
+/// 设置插件
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(load_settings_system.in_base_set(StartupSet::PreStartup))
+            .add_startup_system(spawn_fps_system.in_base_set(StartupSet::PostStartup))
+            .add_system(apply_settings_system)
+            .add_system(fps_update_system)
+            .add_system(enter_settings_system.in_set(OnUpdate(GameState::Menu)))
+            .add_system(settings_display_system.in_schedule(OnEnter(GameState::Settings)))
+            .add_systems(
+                (settings_input_system, settings_text_update_system)
+                    .in_set(OnUpdate(GameState::Settings)),
+            )
+            .add_system(exit_settings_system.in_schedule(OnExit(GameState::Settings)));
+    }
+}
+
+/// 设置项
+#[derive(Clone, Copy)]
+enum SettingItem {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Fullscreen,
+    Vsync,
+    WindowScale,
+    ScaleMode,
+    Difficulty,
+    ShowFps,
+    Effects,
+}
+
+impl SettingItem {
+    const ALL: [SettingItem; 10] = [
+        SettingItem::MasterVolume,
+        SettingItem::SfxVolume,
+        SettingItem::MusicVolume,
+        SettingItem::Fullscreen,
+        SettingItem::Vsync,
+        SettingItem::WindowScale,
+        SettingItem::ScaleMode,
+        SettingItem::Difficulty,
+        SettingItem::ShowFps,
+        SettingItem::Effects,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingItem::MasterVolume => "MASTER VOLUME",
+            SettingItem::SfxVolume => "SFX VOLUME",
+            SettingItem::MusicVolume => "MUSIC VOLUME",
+            SettingItem::Fullscreen => "FULLSCREEN",
+            SettingItem::Vsync => "VSYNC",
+            SettingItem::WindowScale => "WINDOW SCALE",
+            SettingItem::ScaleMode => "SCALING",
+            SettingItem::Difficulty => "DIFFICULTY",
+            SettingItem::ShowFps => "SHOW FPS",
+            SettingItem::Effects => "EFFECTS",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let on_off = |value: bool| if value { "ON" } else { "OFF" }.to_owned();
+        match self {
+            SettingItem::MasterVolume => format!("{:.0}%", settings.master_volume * 100.),
+            SettingItem::SfxVolume => format!("{:.0}%", settings.sfx_volume * 100.),
+            SettingItem::MusicVolume => format!("{:.0}%", settings.music_volume * 100.),
+            SettingItem::Fullscreen => on_off(settings.fullscreen),
+            SettingItem::Vsync => on_off(settings.vsync),
+            SettingItem::WindowScale => format!("{}X", settings.window_scale),
+            SettingItem::ScaleMode => settings.scale_mode.label().to_owned(),
+            SettingItem::Difficulty => settings.difficulty.label().to_owned(),
+            SettingItem::ShowFps => on_off(settings.show_fps),
+            SettingItem::Effects => on_off(settings.effects),
+        }
+    }
+
+    /// 调整设置项，`direction` 为 1 或 -1
+    fn adjust(&self, settings: &mut Settings, direction: isize) {
+        match self {
+            SettingItem::MasterVolume => {
+                settings.master_volume =
+                    Settings::step_volume(settings.master_volume, direction as f32)
+            }
+            SettingItem::SfxVolume => {
+                settings.sfx_volume = Settings::step_volume(settings.sfx_volume, direction as f32)
+            }
+            SettingItem::MusicVolume => {
+                settings.music_volume =
+                    Settings::step_volume(settings.music_volume, direction as f32)
+            }
+            SettingItem::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingItem::Vsync => settings.vsync = !settings.vsync,
+            SettingItem::WindowScale => settings.step_window_scale(direction),
+            SettingItem::ScaleMode => settings.scale_mode = settings.scale_mode.toggle(),
+            SettingItem::Difficulty => {
+                settings.difficulty = if direction > 0 {
+                    settings.difficulty.next()
+                } else {
+                    settings.difficulty.prev()
+                }
+            }
+            SettingItem::ShowFps => settings.show_fps = !settings.show_fps,
+            SettingItem::Effects => settings.effects = !settings.effects,
+        }
+    }
+}
+
+/// 读取本地保存的设置，启动参数优先
+fn load_settings_system(
+    mut commands: Commands,
+    storage: Res<Storage>,
+    options: Option<Res<LaunchOptions>>,
+) {
+    let mut settings: Settings = storage.load(SETTINGS_FILE_NAME);
+    if let Some(options) = options {
+        options.apply(&mut settings);
+    }
+    commands.insert_resource(settings);
+}
+
+/// 设置发生变化时立即应用并保存
+fn apply_settings_system(
+    settings: Res<Settings>,
+    storage: Res<Storage>,
+    options: Option<Res<LaunchOptions>>,
+    mut windows: Query<&mut Window>,
+    mut fps_query: Query<&mut Visibility, With<DisplayFps>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut window in windows.iter_mut() {
+        window.mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        let scale_factor = window.resolution.base_scale_factor() * settings.window_scale as f64;
+        window
+            .resolution
+            .set_scale_factor_override(Some(scale_factor));
+    }
+    for mut visibility in fps_query.iter_mut() {
+        *visibility = if settings.show_fps {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    // 刚读取的设置不需要再写回
+    if settings.is_added() {
+        return;
+    }
+    // 启动参数只对本次启动生效，保存时恢复设置文件中的值
+    match options {
+        Some(options) => {
+            let stored = storage.load(SETTINGS_FILE_NAME);
+            storage.save(
+                SETTINGS_FILE_NAME,
+                &options.remove_overrides(&settings, &stored),
+            );
+        }
+        None => storage.save(SETTINGS_FILE_NAME, &*settings),
+    }
+}
+
+/// 生成帧率显示
+fn spawn_fps_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    settings: Res<Settings>,
+    static_assets: Res<StaticAssets>,
+) {
+    let style = TextStyle {
+        font: static_assets.kenney_future_font.clone(),
+        font_size: 18.,
+        color: Color::WHITE,
+    };
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(vec![
+                TextSection::new("FPS: ".to_owned(), style.clone()),
+                TextSection::new("-".to_owned(), style),
+            ]),
+            text_anchor: Anchor::TopLeft,
+            transform: Transform {
+                translation: Vec3::new(-win_size.width / 2. + 8., win_size.height / 2. - 8., 10.),
+                ..Default::default()
+            },
+            visibility: if settings.show_fps {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..Default::default()
+        },
+        DisplayFps,
+    ));
+}
+
+/// 帧率更新系统
+fn fps_update_system(
+    settings: Res<Settings>,
+    diagnostics: Res<Diagnostics>,
+    mut query: Query<&mut Text, With<DisplayFps>>,
+) {
+    if !settings.show_fps {
+        return;
+    }
+    let fps = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed());
+    for mut text in query.iter_mut() {
+        text.sections[1].value = match fps {
+            Some(fps) => format!("{:.0}", fps),
+            None => "-".to_owned(),
+        };
+    }
+}
+
+/// 菜单中进入设置
+fn enter_settings_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if kb.just_released(KeyCode::S) {
+        next_state.set(GameState::Settings);
+    }
+}
+
+/// 进入设置页面
+fn settings_display_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    static_assets: Res<StaticAssets>,
+) {
+    let display = DisplaySettings::default();
+    let sections = settings_sections(&settings, display.selected, &static_assets);
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_sections(sections).with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(0., 40., 4.),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        display,
+    ));
+}
+
+/// 设置页面的键盘监听
+///
+/// * 上下切换设置项，左右调整数值，S 或退格键返回菜单
+fn settings_input_system(
+    kb: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut query: Query<&mut DisplaySettings>,
+) {
+    if kb.just_released(KeyCode::S) || kb.just_released(KeyCode::Back) {
+        next_state.set(GameState::Menu);
+        return;
+    }
+    let len = SettingItem::ALL.len();
+    for mut display in query.iter_mut() {
+        if kb.just_pressed(KeyCode::Up) {
+            display.selected = (display.selected + len - 1) % len;
+        } else if kb.just_pressed(KeyCode::Down) {
+            display.selected = (display.selected + 1) % len;
+        }
+
+        let item = SettingItem::ALL[display.selected];
+        if kb.just_pressed(KeyCode::Left) {
+            item.adjust(&mut settings, -1);
+        } else if kb.just_pressed(KeyCode::Right) {
+            item.adjust(&mut settings, 1);
+        }
+    }
+}
+
+/// 设置项或选中项变化时刷新文本
+fn settings_text_update_system(
+    settings: Res<Settings>,
+    static_assets: Res<StaticAssets>,
+    mut query: Query<(Ref<DisplaySettings>, &mut Text)>,
+) {
+    for (display, mut text) in query.iter_mut() {
+        if settings.is_changed() || display.is_changed() {
+            text.sections = settings_sections(&settings, display.selected, &static_assets);
+        }
+    }
+}
+
+/// 退出设置页面
+fn exit_settings_system(mut commands: Commands, query: Query<Entity, With<DisplaySettings>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 生成设置页面的文本
+fn settings_sections(
+    settings: &Settings,
+    selected: usize,
+    static_assets: &StaticAssets,
+) -> Vec<TextSection> {
+    let font = static_assets.kenney_future_font.clone();
+    let common_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.,
+        color: Color::BLUE,
+    };
+    let special_style = TextStyle {
+        font,
+        font_size: 26.,
+        color: Color::RED,
+    };
+
+    let mut sections = vec![TextSection::new(
+        "SETTINGS\r\n\r\n".to_owned(),
+        special_style.clone(),
+    )];
+    for (index, item) in SettingItem::ALL.iter().enumerate() {
+        let line = format!("{}  < {} >\r\n", item.label(), item.value(settings));
+        let style = if index == selected {
+            special_style.clone()
+        } else {
+            common_style.clone()
+        };
+        sections.push(TextSection::new(line, style));
+    }
+    sections.push(TextSection::new(
+        "\r\nS BACK TO MENU".to_owned(),
+        common_style,
+    ));
+    sections
+}
@@ -0,0 +1,80 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 计算 HMAC-SHA256
+fn hmac_sha256(key: &[u8], message: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC 支持任意长度的密钥");
+    mac.update(message);
+    mac
+}
+
+/// 用 HMAC-SHA256 签名，返回十六进制字符串
+///
+/// * 排行榜提交的记录使用这个签名
+pub fn sign(key: &str, message: &str) -> String {
+    encode_hex(
+        &hmac_sha256(key.as_bytes(), message.as_bytes())
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+/// 验证签名，比较时间与签名内容无关
+pub fn verify(key: &str, message: &str, signature: &str) -> bool {
+    let Some(signature) = decode_hex(signature) else {
+        return false;
+    };
+    hmac_sha256(key.as_bytes(), message.as_bytes())
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 解析十六进制字符串，格式错误时返回 `None`
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let long_key = [0xaa; 131];
+        let mac = hmac_sha256(
+            &long_key,
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+        );
+        assert_eq!(
+            encode_hex(&mac.finalize().into_bytes()),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_changed_messages() {
+        let signature = sign("key", "score 10");
+        assert!(verify("key", "score 10", &signature));
+        assert!(!verify("key", "score 99", &signature));
+        assert!(!verify("other", "score 10", &signature));
+        assert!(!verify("key", "score 10", &signature[1..]));
+        assert!(!verify("key", "score 10", &signature[2..]));
+        assert!(!verify("key", "score 10", "zz"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    time::{TimeSystem, TimeUpdateStrategy},
+    window::WindowFocused,
+};
+
+use crate::{
+    components::{Obstacle, Player},
+    constants::{TIME_STEP, WINDOW_HEIGHT, WINDOW_WIDTH},
+    death::DeathPlugin,
+    events::{GameEventsPlugin, RunStats},
+    gameplay::GameplayPlugin,
+    obstacle::ObstaclePlugin,
+    pause::{PausePlugin, PauseState},
+    player::PlayerPlugin,
+    replay::Replay,
+    resource::{GameData, GameRng, Settings, StaticAssets, WinSize},
+    state::{GameState, StatesPlugin},
+    storage::Storage,
+    theme::ThemePlugin,
+};
+
+/// 不需要窗口和渲染的游戏
+///
+/// * 只包含影响玩法的插件，没有练习工具、声音、背景和粒子；主题的风力会推动小鸟，所以包含主题
+/// * 每帧固定前进 `TIME_STEP` 秒，使用固定种子的随机数，相同的输入得到相同的结果
+/// * 不读写本地文件
+pub fn headless_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.add_plugin(FixedStepPlugin)
+        .init_resource::<Input<KeyCode>>()
+        .add_event::<WindowFocused>()
+        .add_state::<GameState>()
+        .insert_resource(GameData::new())
+        .insert_resource(GameRng::from_seed(seed))
+        .insert_resource(WinSize {
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            scale: 1.,
+        })
+        .insert_resource(StaticAssets::default())
+        .init_resource::<Settings>()
+        .init_resource::<Storage>()
+        .add_plugin(GameEventsPlugin)
+        .add_plugin(StatesPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(DeathPlugin)
+        .add_plugin(PlayerPlugin)
+        .add_plugin(ObstaclePlugin)
+        .add_plugin(GameplayPlugin)
+        .add_plugin(ThemePlugin);
+    app
+}
+
+/// 固定步长插件
+///
+/// * 每帧时间固定前进 `TIME_STEP` 秒，与真实的帧率无关，用于无界面模拟、录制和回放
+pub struct FixedStepPlugin;
+
+impl Plugin for FixedStepPlugin {
+    fn build(&self, app: &mut App) {
+        let startup = app.world.resource::<Time>().startup();
+        app.insert_resource(TimeUpdateStrategy::ManualInstant(startup))
+            .add_system(
+                advance_clock_system
+                    .in_base_set(CoreSet::First)
+                    .before(TimeSystem),
+            );
+    }
+}
+
+/// 每帧时间固定前进 `TIME_STEP` 秒，与真实时间无关
+fn advance_clock_system(mut strategy: ResMut<TimeUpdateStrategy>) {
+    if let TimeUpdateStrategy::ManualInstant(instant) = strategy.as_mut() {
+        *instant += Duration::from_secs_f32(TIME_STEP);
+    }
+}
+
+/// 逐帧推进的游戏模拟
+///
+/// * 按脚本输入按键，检查游戏状态、分数和实体的位置
+pub struct Simulation {
+    app: App,
+    seed: u64,
+    frame: u32,
+    /// 每次飞行时的帧数
+    flaps: Vec<u32>,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            app: headless_app(seed),
+            seed,
+            frame: 0,
+            flaps: vec![],
+        }
+    }
+
+    /// 直接访问 `App`
+    pub fn app(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// 已经推进的帧数
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// 推进一帧
+    pub fn step(&mut self) {
+        self.app.update();
+        self.app.world.resource_mut::<Input<KeyCode>>().clear();
+        self.frame += 1;
+    }
+
+    /// 推进多帧
+    pub fn step_n(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// 在这一帧按下并松开按键，然后推进一帧
+    pub fn tap(&mut self, key: KeyCode) {
+        let mut input = self.app.world.resource_mut::<Input<KeyCode>>();
+        input.press(key);
+        input.release(key);
+        self.step();
+    }
+
+    /// 飞行一次
+    pub fn flap(&mut self) {
+        self.flaps.push(self.frame);
+        self.tap(KeyCode::Space);
+    }
+
+    /// 到目前为止的输入，可以保存下来重现这一局
+    ///
+    /// * 菜单和准备状态下的空格也会记录，回放时从第 0 帧开始
+    pub fn replay(&self) -> Replay {
+        Replay {
+            seed: self.seed,
+            difficulty: self.app.world.resource::<Settings>().difficulty,
+            flaps: self.flaps.clone(),
+        }
+    }
+
+    /// 从菜单开始新的一局，第一次飞行后进入游戏
+    pub fn start_run(&mut self) {
+        // 启动系统
+        self.step();
+        self.flap();
+        self.step();
+        self.flap();
+        self.step();
+    }
+
+    /// 推进直到满足条件，返回推进的帧数，超过 `max_frames` 时返回 `None`
+    pub fn run_until(
+        &mut self,
+        max_frames: u32,
+        mut condition: impl FnMut(&mut Self) -> bool,
+    ) -> Option<u32> {
+        for frames in 0..=max_frames {
+            if condition(self) {
+                return Some(frames);
+            }
+            if frames < max_frames {
+                self.step();
+            }
+        }
+        None
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app.world.resource::<State<GameState>>().0.clone()
+    }
+
+    pub fn pause_state(&self) -> PauseState {
+        self.app.world.resource::<State<PauseState>>().0.clone()
+    }
+
+    /// 当前的得分，进入游戏结束状态后清零
+    pub fn score(&self) -> u8 {
+        self.app.world.resource::<GameData>().get_score()
+    }
+
+    /// 这一局的统计，游戏结束后仍然保留
+    pub fn run_stats(&self) -> &RunStats {
+        self.app.world.resource::<RunStats>()
+    }
+
+    /// 小鸟的位置
+    pub fn bird(&mut self) -> Option<Vec3> {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .iter(&self.app.world)
+            .next()
+            .map(|transform| transform.translation)
+    }
+
+    /// 所有管道的位置，按坐标排序
+    pub fn obstacles(&mut self) -> Vec<Vec3> {
+        let mut obstacles: Vec<_> = self
+            .app
+            .world
+            .query_filtered::<&Transform, With<Obstacle>>()
+            .iter(&self.app.world)
+            .map(|transform| transform.translation)
+            .collect();
+        obstacles.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        obstacles
+    }
+
+    /// 带有某个组件的实体数量
+    pub fn count<T: Component>(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<T>>()
+            .iter(&self.app.world)
+            .count()
+    }
+}
@@ -1,64 +1,88 @@
+use std::f32::consts::FRAC_PI_4;
+
 use bevy::{
     prelude::{
-        Color, Commands, Entity, Input, IntoSystemAppConfig, IntoSystemConfig, KeyCode, NextState,
-        OnEnter, OnExit, OnUpdate, Plugin, Query, Res, ResMut, States, Transform, Vec3, With,
+        BuildChildren, Color, Commands, DespawnRecursiveExt, Entity, EventWriter, Input,
+        IntoSystemAppConfig, IntoSystemConfig, IntoSystemConfigs, KeyCode, NextState, OnEnter,
+        OnExit, OnUpdate, Plugin, Quat, Query, Res, ResMut, SpatialBundle, StartupSet, States,
+        Transform, Vec2, Vec3, Visibility, With,
     },
+    sprite::{Sprite, SpriteBundle},
     text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    time::{Time, Timer, TimerMode},
 };
 
 use crate::{
-    components::{DisplayGameOver, DisplayMenu, DisplayScore, Obstacle, Player},
-    constants::GROUND_IMG_SIZE,
-    resource::{GameData, StaticAssets, WinSize},
+    components::{
+        CountUp, DisplayGameOver, DisplayMenu, DisplayReady, DisplayScore, Obstacle, Player,
+        RevealAfterCountUp, SlideIn,
+    },
+    constants::{
+        GAME_OVER_SLIDE_SECONDS, GROUND_IMG_SIZE, RECORDS_FILE_NAME, SCORE_COUNT_UP_SECONDS,
+    },
+    events::{RunStarted, RunStats},
+    resource::{GameData, Medal, Records, StaticAssets, WinSize},
+    storage::Storage,
 };
 
 #[derive(Debug, Default, States, PartialEq, Eq, Clone, Hash)]
 pub enum GameState {
     #[default]
     Menu,
+    /// 开始游戏前的准备
+    Ready,
     InGame,
-    Paused,
+    /// 撞击后播放死亡动画
+    Dying,
     GameOver,
+    Settings,
+    /// 成就列表
+    Achievements,
 }
 
 pub struct StatesPlugin;
 
 impl Plugin for StatesPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app
+        app.add_startup_system(load_records_system.in_base_set(StartupSet::PreStartup))
             //菜单状态
             .add_system(menu_display_system.in_schedule(OnEnter(GameState::Menu)))
             .add_system(enter_game_system.in_set(OnUpdate(GameState::Menu)))
             .add_system(exit_menu.in_schedule(OnExit(GameState::Menu)))
-            // 暂停状态
-            .add_system(enter_paused_system.in_schedule(OnEnter(GameState::Paused)))
-            .add_system(paused_input_system.in_set(OnUpdate(GameState::Paused)))
-            .add_system(paused_exit_system.in_schedule(OnExit(GameState::Paused)))
+            // 准备状态
+            .add_system(ready_display_system.in_schedule(OnEnter(GameState::Ready)))
+            .add_system(start_run_system.in_schedule(OnEnter(GameState::Ready)))
+            .add_system(ready_input_system.in_set(OnUpdate(GameState::Ready)))
+            .add_system(ready_exit_system.in_schedule(OnExit(GameState::Ready)))
             // 游戏中状态
             .add_system(in_game_display_system.in_schedule(OnEnter(GameState::InGame)))
-            .add_system(in_game_input_system.in_set(OnUpdate(GameState::InGame)))
             .add_system(exit_game_system.in_schedule(OnExit(GameState::InGame)))
             // 游戏结束状态
             .add_system(game_over_enter_system.in_schedule(OnEnter(GameState::GameOver)))
-            .add_system(in_game_over_system.in_set(OnUpdate(GameState::GameOver)))
+            .add_systems(
+                (
+                    game_over_slide_system,
+                    score_count_up_system,
+                    in_game_over_system,
+                )
+                    .in_set(OnUpdate(GameState::GameOver)),
+            )
             .add_system(game_over_exit_system.in_schedule(OnExit(GameState::GameOver)));
     }
 }
 
-//// 进入菜单页面
+/// 进入菜单页面
 fn menu_display_system(mut commands: Commands, static_assets: Res<StaticAssets>) {
     let font = static_assets.kenney_future_font.clone();
     let common_style = TextStyle {
         font: font.clone(),
         font_size: 32.,
         color: Color::BLUE,
-        ..Default::default()
     };
     let special_style = TextStyle {
         font: font.clone(),
         font_size: 38.,
         color: Color::RED,
-        ..Default::default()
     };
 
     let align = TextAlignment::Center;
@@ -70,6 +94,10 @@ fn menu_display_system(mut commands: Commands, static_assets: Res<StaticAssets>)
                 TextSection::new("START GAME!\r\n".to_owned(), common_style.clone()),
                 TextSection::new(" P \r\n".to_owned(), special_style.clone()),
                 TextSection::new("PAUSED GAME!\r\n".to_owned(), common_style.clone()),
+                TextSection::new(" S \r\n".to_owned(), special_style.clone()),
+                TextSection::new("SETTINGS!\r\n".to_owned(), common_style.clone()),
+                TextSection::new(" A \r\n".to_owned(), special_style.clone()),
+                TextSection::new("ACHIEVEMENTS!\r\n".to_owned(), common_style.clone()),
             ])
             .with_alignment(align),
             transform: Transform {
@@ -82,7 +110,7 @@ fn menu_display_system(mut commands: Commands, static_assets: Res<StaticAssets>)
     ));
 }
 
-//// 进入游戏显示系统
+/// 进入游戏显示系统
 fn in_game_display_system(
     mut commands: Commands,
     win_size: Res<WinSize>,
@@ -93,13 +121,11 @@ fn in_game_display_system(
         font: font.clone(),
         font_size: 32.,
         color: Color::BLUE,
-        ..Default::default()
     };
     let special_style = TextStyle {
         font: font.clone(),
         font_size: 38.,
         color: Color::RED,
-        ..Default::default()
     };
     let y = -(win_size.height / 2. - GROUND_IMG_SIZE.1 + special_style.font_size * 1.5);
     let align = TextAlignment::Center;
@@ -123,142 +149,288 @@ fn in_game_display_system(
 /// 进入游戏
 fn enter_game_system(kb: Res<Input<KeyCode>>, mut state: ResMut<NextState<GameState>>) {
     if kb.just_released(KeyCode::Space) {
-        state.set(GameState::InGame)
+        state.set(GameState::Ready)
     }
 }
 
-/// 退出游戏
-fn exit_game_system(
-    mut commands: Commands,
-    query: Query<Entity, (With<Text>, With<DisplayScore>)>,
-) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
-    }
-}
-/// 退出菜单
-fn exit_menu(mut commands: Commands, query: Query<Entity, (With<Text>, With<DisplayMenu>)>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
-    }
-}
-
-/// 进入暂停状态下运行的系统
-pub fn enter_paused_system(mut commands: Commands, static_assets: Res<StaticAssets>) {
-    // 字体引入
+/// 进入准备状态，显示操作提示
+fn ready_display_system(mut commands: Commands, static_assets: Res<StaticAssets>) {
     let font = static_assets.kenney_future_font.clone();
     let common_style = TextStyle {
         font: font.clone(),
         font_size: 32.,
         color: Color::BLUE,
-        ..Default::default()
     };
     let special_style = TextStyle {
         font: font.clone(),
         font_size: 38.,
         color: Color::RED,
-        ..Default::default()
     };
 
     let align = TextAlignment::Center;
     commands.spawn((
         Text2dBundle {
             text: Text::from_sections(vec![
-                TextSection::new("PAUSED  \r\n".to_owned(), common_style.clone()),
-                TextSection::new(" R \r\n".to_owned(), special_style.clone()),
-                TextSection::new("RETURN GAME!".to_owned(), common_style.clone()),
+                TextSection::new("GET READY!\r\n".to_owned(), common_style.clone()),
+                TextSection::new(" SPACE \r\n".to_owned(), special_style),
+                TextSection::new("TO FLAP".to_owned(), common_style),
             ])
             .with_alignment(align),
             transform: Transform {
-                translation: Vec3::new(0., 0., 4.),
+                // 显示在小鸟下方
+                translation: Vec3::new(0., -40., 6.),
                 ..Default::default()
             },
             ..Default::default()
         },
-        DisplayMenu,
+        DisplayReady,
     ));
 }
 
-/// 暂停状态状态下的键盘监听系统
-pub fn paused_input_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
-    if kb.pressed(KeyCode::R) {
+/// 开始新的一局
+fn start_run_system(mut started_events: EventWriter<RunStarted>) {
+    started_events.send(RunStarted);
+}
+
+/// 第一次飞行后开始游戏
+///
+/// * 飞行本身由玩家插件处理
+fn ready_input_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if kb.just_released(KeyCode::Space) {
         next_state.set(GameState::InGame);
     }
 }
 
-/// 退出暂停状态时执行的系统
-pub fn paused_exit_system(
+/// 退出准备状态
+fn ready_exit_system(mut commands: Commands, query: Query<Entity, With<DisplayReady>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 退出游戏
+fn exit_game_system(
     mut commands: Commands,
-    query: Query<Entity, (With<Text>, With<DisplayMenu>)>,
+    query: Query<Entity, (With<Text>, With<DisplayScore>)>,
 ) {
     for entity in query.iter() {
         commands.entity(entity).despawn();
     }
 }
-
-/// 游戏中监听暂停
-pub fn in_game_input_system(kb: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
-    if kb.pressed(KeyCode::P) {
-        next_state.set(GameState::Paused);
+/// 退出菜单
+fn exit_menu(mut commands: Commands, query: Query<Entity, (With<Text>, With<DisplayMenu>)>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
     }
 }
 
+/// 读取本地保存的记录
+fn load_records_system(mut commands: Commands, storage: Res<Storage>) {
+    let records: Records = storage.load(RECORDS_FILE_NAME);
+    commands.insert_resource(records);
+}
+
 /// 游戏结束状态下运行的系统
+///
+/// * 结算面板：奖牌、本局分数和最高分，打破记录时显示 NEW
 pub fn game_over_enter_system(
     mut commands: Commands,
     game_data: Res<GameData>,
+    win_size: Res<WinSize>,
     static_assets: Res<StaticAssets>,
+    storage: Res<Storage>,
+    run: Res<RunStats>,
+    mut records: ResMut<Records>,
 ) {
+    let score = game_data.get_score();
+    // 练习的成绩不计入记录
+    let new_record = !run.practice && records.submit(score);
+    if new_record {
+        storage.save(RECORDS_FILE_NAME, &*records);
+    }
+
     // 字体引入
     let font = static_assets.kenney_future_font.clone();
     let common_style = TextStyle {
         font: font.clone(),
         font_size: 32.,
         color: Color::BLUE,
-        ..Default::default()
     };
     let special_style = TextStyle {
         font: font.clone(),
         font_size: 38.,
         color: Color::RED,
-        ..Default::default()
+    };
+    let label_style = TextStyle {
+        font: font.clone(),
+        font_size: 20.,
+        color: Color::BLUE,
     };
 
     let align = TextAlignment::Center;
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_sections(vec![
-                TextSection::new(
-                    "GAME OVER !  \r\n You got ".to_owned(),
-                    common_style.clone(),
-                ),
-                TextSection::new(game_data.get_score().to_string(), special_style.clone()),
-                TextSection::new(" score. \r\n  ".to_owned(), common_style.clone()),
-                TextSection::new("SPACE ".to_owned(), special_style.clone()),
-                TextSection::new("RESTART GAME! \r\n".to_owned(), common_style.clone()),
-                TextSection::new("M ".to_owned(), special_style.clone()),
-                TextSection::new("TO MENU".to_owned(), common_style.clone()),
-            ])
-            .with_alignment(align),
-            transform: Transform {
-                translation: Vec3::new(0., 80., 4.),
-                ..Default::default()
+    let text = |sections: Vec<TextSection>, x: f32, y: f32| Text2dBundle {
+        text: Text::from_sections(sections).with_alignment(align),
+        transform: Transform::from_xyz(x, y, 1.),
+        ..Default::default()
+    };
+    let medal = Medal::from_score(score);
+
+    // 从画面上方滑入，面板中的内容跟随移动
+    let from = win_size.height;
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0., from, 4.)),
+            DisplayGameOver,
+            SlideIn {
+                from,
+                to: 60.,
+                timer: Timer::from_seconds(GAME_OVER_SLIDE_SECONDS, TimerMode::Once),
             },
-            ..Default::default()
-        },
-        DisplayGameOver,
-    ));
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(
+                vec![TextSection::new(
+                    "GAME OVER !".to_owned(),
+                    special_style.clone(),
+                )],
+                0.,
+                150.,
+            ));
+            // 面板背景
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1., 1., 1., 0.8),
+                    custom_size: Some(Vec2::new(360., 160.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+
+            // 左侧奖牌，分数不够时显示空位
+            parent.spawn(text(
+                vec![TextSection::new("MEDAL".to_owned(), label_style.clone())],
+                -100.,
+                55.,
+            ));
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0., 0., 0., 0.15),
+                    custom_size: Some(Vec2::splat(56.)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(-100., -5., 1.),
+                    rotation: Quat::from_rotation_z(FRAC_PI_4),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            if let Some(medal) = medal {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: medal.color(),
+                            custom_size: Some(Vec2::splat(48.)),
+                            ..Default::default()
+                        },
+                        transform: Transform {
+                            translation: Vec3::new(-100., -5., 2.),
+                            rotation: Quat::from_rotation_z(FRAC_PI_4),
+                            ..Default::default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..Default::default()
+                    },
+                    RevealAfterCountUp,
+                ));
+                parent.spawn((
+                    Text2dBundle {
+                        visibility: Visibility::Hidden,
+                        ..text(
+                            vec![TextSection::new(
+                                medal.label().to_owned(),
+                                label_style.clone(),
+                            )],
+                            -100.,
+                            -60.,
+                        )
+                    },
+                    RevealAfterCountUp,
+                ));
+            }
+
+            // 右侧本局分数和最高分
+            parent.spawn(text(
+                vec![TextSection::new("SCORE".to_owned(), label_style.clone())],
+                80.,
+                55.,
+            ));
+            parent.spawn((
+                text(
+                    vec![TextSection::new("0".to_owned(), special_style.clone())],
+                    80.,
+                    25.,
+                ),
+                CountUp {
+                    target: score,
+                    timer: Timer::from_seconds(SCORE_COUNT_UP_SECONDS, TimerMode::Once),
+                },
+            ));
+            parent.spawn(text(
+                vec![TextSection::new("BEST".to_owned(), label_style.clone())],
+                80.,
+                -15.,
+            ));
+            parent.spawn(text(
+                vec![TextSection::new(
+                    records.best.to_string(),
+                    special_style.clone(),
+                )],
+                80.,
+                -45.,
+            ));
+            if new_record {
+                parent.spawn((
+                    Text2dBundle {
+                        visibility: Visibility::Hidden,
+                        ..text(
+                            vec![TextSection::new(
+                                "NEW".to_owned(),
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    ..label_style.clone()
+                                },
+                            )],
+                            145.,
+                            -45.,
+                        )
+                    },
+                    RevealAfterCountUp,
+                ));
+            }
+
+            parent.spawn(text(
+                vec![
+                    TextSection::new("SPACE ".to_owned(), special_style.clone()),
+                    TextSection::new("RESTART GAME! \r\n".to_owned(), common_style.clone()),
+                    TextSection::new("M ".to_owned(), special_style.clone()),
+                    TextSection::new("TO MENU".to_owned(), common_style.clone()),
+                ],
+                0.,
+                -140.,
+            ));
+        });
 }
 
 /// 退出游戏状态时执行的系统
 pub fn game_over_exit_system(
     mut commands: Commands,
-    query: Query<Entity, (With<Text>, With<DisplayGameOver>)>,
+    query: Query<Entity, With<DisplayGameOver>>,
     obstacle_query: Query<Entity, With<Obstacle>>,
     player_query: Query<Entity, With<Player>>,
 ) {
     for entity in query.iter() {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
     for entity in obstacle_query.iter() {
         commands.entity(entity).despawn();
@@ -268,16 +440,57 @@ pub fn game_over_exit_system(
     }
 }
 
+/// 游戏结束面板滑入
+pub fn game_over_slide_system(time: Res<Time>, mut query: Query<(&mut SlideIn, &mut Transform)>) {
+    for (mut slide, mut transform) in query.iter_mut() {
+        slide.timer.tick(time.delta());
+        // 缓出，越接近目标越慢
+        let t = 1. - (1. - slide.timer.percent()).powi(3);
+        transform.translation.y = slide.from + (slide.to - slide.from) * t;
+    }
+}
+
+/// 面板滑入后分数从 0 开始滚动，结束后显示奖牌和新纪录标记
+pub fn score_count_up_system(
+    time: Res<Time>,
+    slide_query: Query<&SlideIn>,
+    mut count_query: Query<(&mut CountUp, &mut Text)>,
+    mut reveal_query: Query<&mut Visibility, With<RevealAfterCountUp>>,
+) {
+    if slide_query.iter().any(|slide| !slide.timer.finished()) {
+        return;
+    }
+    for (mut count_up, mut text) in count_query.iter_mut() {
+        if count_up.timer.finished() {
+            continue;
+        }
+        count_up.timer.tick(time.delta());
+        let value = (count_up.target as f32 * count_up.timer.percent()).round() as u8;
+        text.sections[0].value = value.to_string();
+        if count_up.timer.finished() {
+            for mut visibility in reveal_query.iter_mut() {
+                *visibility = Visibility::Inherited;
+            }
+        }
+    }
+}
+
 /// 退出游戏状态监听
+///
+/// * 面板滑入完成之前不响应按键，避免误操作跳过
 pub fn in_game_over_system(
     kb: Res<Input<KeyCode>>,
     mut game_data: ResMut<GameData>,
     mut next_state: ResMut<NextState<GameState>>,
+    slide_query: Query<&SlideIn>,
 ) {
     game_data.death();
+    if slide_query.iter().any(|slide| !slide.timer.finished()) {
+        return;
+    }
     if kb.pressed(KeyCode::M) {
         next_state.set(GameState::Menu);
-    } else if kb.pressed(KeyCode::Space) {
-        next_state.set(GameState::InGame);
+    } else if kb.just_released(KeyCode::Space) {
+        next_state.set(GameState::Ready);
     }
 }
@@ -0,0 +1,62 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{log::warn, prelude::Resource};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// 存档目录名称
+const STORAGE_DIR_NAME: &str = "flappy_bird_bevy";
+
+/// 本地存储资源
+///
+/// * 所有需要持久化的数据都以 ron 格式写入同一个目录
+/// * `dir` 为 `None` 时不落盘，读取都返回默认值（wasm 或测试环境）
+#[derive(Resource, Clone, Default)]
+pub struct Storage {
+    dir: Option<PathBuf>,
+}
+
+impl Storage {
+    /// 使用系统的配置目录
+    pub fn new() -> Self {
+        Self {
+            dir: dirs::config_dir().map(|dir| dir.join(STORAGE_DIR_NAME)),
+        }
+    }
+
+    /// 文件的完整路径，不落盘时返回 `None`
+    pub fn path(&self, file: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(file))
+    }
+
+    /// 读取文件，文件不存在或者格式错误时返回默认值
+    pub fn load<T: DeserializeOwned + Default>(&self, file: &str) -> T {
+        let Some(dir) = &self.dir else {
+            return T::default();
+        };
+        let path = dir.join(file);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return T::default();
+        };
+        ron::from_str(&content).unwrap_or_else(|err| {
+            warn!("读取 {} 失败: {}", path.display(), err);
+            T::default()
+        })
+    }
+
+    /// 写入文件
+    pub fn save<T: Serialize>(&self, file: &str, value: &T) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let content = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!("序列化 {} 失败: {}", file, err);
+                return;
+            }
+        };
+        if let Err(err) = fs::create_dir_all(dir).and_then(|_| fs::write(dir.join(file), content)) {
+            warn!("写入 {} 失败: {}", file, err);
+        }
+    }
+}
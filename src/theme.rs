@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use bevy::{
+    log::info,
+    prelude::{
+        AnyOf, AssetServer, Color, Commands, Entity, Handle, Image, IntoSystemAppConfig,
+        IntoSystemConfigs, OnExit, OnUpdate, Plugin, Query, Res, ResMut, Resource, Sprite,
+        SpriteBundle, Transform, Vec2, Vec3, With,
+    },
+    time::Time,
+};
+use rand::{thread_rng, Rng};
+use serde::Deserialize;
+
+use crate::{
+    components::{Ground, Movable, Obstacle, ParallaxTile, Player, Velocity, WeatherParticle},
+    config::load_config,
+    constants::{
+        GROUND_IMG_SIZE, RAIN_FALL_VELOCITY, SNOW_FALL_VELOCITY, THEMES_CONFIG_PATH, WIND_DAMPING,
+        WIND_SPRING,
+    },
+    parallax::LayerManifest,
+    pause::GameplaySet,
+    resource::{GameClock, GameData, Settings, WinSize},
+    state::GameState,
+};
+
+/// 主题插件
+///
+/// * 随着分数增加切换白天、黄昏、夜晚等阶段，渐变背景、地面和管道的颜色
+/// * 每个阶段可以带有天气，风力会推动小鸟
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let config: ThemeConfig =
+            load_config(THEMES_CONFIG_PATH, include_str!("../assets/themes.ron"));
+        app.insert_resource(config)
+            .init_resource::<ThemeState>()
+            .add_systems((theme_phase_system, theme_tint_system).chain())
+            .add_systems(
+                (weather_spawn_system, weather_despawn_system, wind_system)
+                    .in_set(GameplaySet)
+                    .in_set(OnUpdate(GameState::InGame)),
+            )
+            .add_system(weather_clear_system.in_schedule(OnExit(GameState::GameOver)));
+    }
+}
+
+/// 主题配置
+#[derive(Resource, Deserialize)]
+pub struct ThemeConfig {
+    /// 当前使用的主题名称
+    active: String,
+    /// 颜色渐变的时长（秒）
+    transition_seconds: f32,
+    themes: Vec<Theme>,
+}
+
+impl ThemeConfig {
+    /// 当前主题的所有阶段，找不到时使用第一个主题
+    fn phases(&self) -> &[ThemePhase] {
+        self.themes
+            .iter()
+            .find(|theme| theme.name == self.active)
+            .or(self.themes.first())
+            .map(|theme| theme.phases.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// 主题
+#[derive(Deserialize)]
+struct Theme {
+    name: String,
+    phases: Vec<ThemePhase>,
+}
+
+/// 主题中的一个阶段
+#[derive(Deserialize)]
+struct ThemePhase {
+    name: String,
+    /// 达到该分数后进入这个阶段
+    score: u8,
+    background: (f32, f32, f32),
+    ground: (f32, f32, f32),
+    pipe: (f32, f32, f32),
+    /// 按名称替换图层的图片
+    #[serde(default)]
+    layer_images: HashMap<String, String>,
+    #[serde(default)]
+    weather: Option<Weather>,
+}
+
+impl ThemePhase {
+    fn tint(&self) -> Tint {
+        Tint {
+            background: Vec3::from(self.background),
+            ground: Vec3::from(self.ground),
+            pipe: Vec3::from(self.pipe),
+        }
+    }
+}
+
+/// 天气
+#[derive(Deserialize, Clone, Copy)]
+struct Weather {
+    kind: WeatherKind,
+    /// 每秒生成的粒子数
+    rate: f32,
+    /// 水平风力，正数向右
+    wind: f32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum WeatherKind {
+    Rain,
+    Snow,
+}
+
+/// 背景、地面和管道的颜色
+#[derive(Clone, Copy)]
+struct Tint {
+    background: Vec3,
+    ground: Vec3,
+    pipe: Vec3,
+}
+
+impl Default for Tint {
+    fn default() -> Self {
+        Self {
+            background: Vec3::ONE,
+            ground: Vec3::ONE,
+            pipe: Vec3::ONE,
+        }
+    }
+}
+
+impl Tint {
+    fn lerp(&self, other: &Tint, t: f32) -> Tint {
+        Tint {
+            background: self.background.lerp(other.background, t),
+            ground: self.ground.lerp(other.ground, t),
+            pipe: self.pipe.lerp(other.pipe, t),
+        }
+    }
+}
+
+/// 主题的运行状态
+#[derive(Resource, Default)]
+pub struct ThemeState {
+    /// 当前阶段，还没有进入任何阶段时为 `None`
+    phase: Option<usize>,
+    /// 渐变开始时的颜色
+    from: Tint,
+    /// 渐变进度 0 ~ 1
+    progress: f32,
+    /// 还没生成的天气粒子
+    pending_particles: f32,
+}
+
+impl ThemeState {
+    fn weather(&self, config: &ThemeConfig) -> Option<Weather> {
+        let phase = config.phases().get(self.phase?)?;
+        phase.weather
+    }
+
+    fn wind(&self, config: &ThemeConfig) -> f32 {
+        self.weather(config).map_or(0., |weather| weather.wind)
+    }
+}
+
+/// 根据分数切换阶段
+fn theme_phase_system(
+    config: Res<ThemeConfig>,
+    game_data: Res<GameData>,
+    layers: Option<Res<LayerManifest>>,
+    asset_server: Option<Res<AssetServer>>,
+    mut state: ResMut<ThemeState>,
+    mut tile_query: Query<(&ParallaxTile, &mut Handle<Image>)>,
+) {
+    let phases = config.phases();
+    let score = game_data.get_score();
+    let Some(phase) = phases.iter().rposition(|phase| phase.score <= score) else {
+        return;
+    };
+    if state.phase == Some(phase) {
+        return;
+    }
+
+    match state.phase {
+        // 从当前显示的颜色开始渐变
+        Some(previous) => {
+            let current = state.from.lerp(&phases[previous].tint(), state.progress);
+            state.from = current;
+            state.progress = 0.;
+        }
+        // 刚启动时直接使用第一个阶段的颜色
+        None => {
+            state.from = phases[phase].tint();
+            state.progress = 1.;
+        }
+    }
+    state.phase = Some(phase);
+    info!("主题阶段切换为 {}", phases[phase].name);
+
+    // 无界面模拟时没有图层和图片
+    let (Some(layers), Some(asset_server)) = (layers, asset_server) else {
+        return;
+    };
+    for (tile, mut texture) in tile_query.iter_mut() {
+        let image = phases[phase]
+            .layer_images
+            .get(&tile.layer)
+            .map(String::as_str)
+            .or_else(|| layers.image(&tile.layer));
+        if let Some(image) = image {
+            *texture = asset_server.load(image);
+        }
+    }
+}
+
+/// 需要上色的实体：背景图层、地面或管道
+type TintTarget = AnyOf<(&'static ParallaxTile, &'static Ground, &'static Obstacle)>;
+
+/// 渐变并应用颜色
+fn theme_tint_system(
+    time: Res<Time>,
+    config: Res<ThemeConfig>,
+    mut state: ResMut<ThemeState>,
+    mut query: Query<(&mut Sprite, TintTarget)>,
+) {
+    let Some(phase) = state.phase.and_then(|phase| config.phases().get(phase)) else {
+        return;
+    };
+    if state.progress < 1. {
+        state.progress = if config.transition_seconds > 0. {
+            (state.progress + time.delta_seconds() / config.transition_seconds).min(1.)
+        } else {
+            1.
+        };
+    }
+    let tint = state.from.lerp(&phase.tint(), state.progress);
+
+    // 新生成的管道也需要上色，所以每一帧都设置
+    for (mut sprite, (background, ground, _)) in query.iter_mut() {
+        let rgb = if background.is_some() {
+            tint.background
+        } else if ground.is_some() {
+            tint.ground
+        } else {
+            tint.pipe
+        };
+        sprite.color = Color::rgb(rgb.x, rgb.y, rgb.z);
+    }
+}
+
+/// 生成天气粒子，设置中关闭特效时不生成
+fn weather_spawn_system(
+    mut commands: Commands,
+    config: Res<ThemeConfig>,
+    settings: Res<Settings>,
+    win_size: Res<WinSize>,
+    clock: Res<GameClock>,
+    mut state: ResMut<ThemeState>,
+) {
+    let weather = state.weather(&config).filter(|_| settings.effects);
+    let Some(weather) = weather else {
+        state.pending_particles = 0.;
+        return;
+    };
+    state.pending_particles += weather.rate * clock.step();
+
+    let mut rng = thread_rng();
+    while state.pending_particles >= 1. {
+        state.pending_particles -= 1.;
+        let (size, color, fall_velocity) = match weather.kind {
+            WeatherKind::Rain => (
+                Vec2::new(2., 12.),
+                Color::rgba(0.7, 0.8, 1., 0.6),
+                RAIN_FALL_VELOCITY,
+            ),
+            WeatherKind::Snow => (Vec2::new(4., 4.), Color::WHITE, SNOW_FALL_VELOCITY),
+        };
+        // 有风时从更宽的范围生成，保证粒子能飘进画面
+        let half_w = win_size.width / 2. + weather.wind.abs();
+        let x = rng.gen_range(-half_w..half_w);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(x, win_size.height / 2. + size.y, 3.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Velocity {
+                x: weather.wind,
+                y: -fall_velocity * rng.gen_range(0.8..1.2),
+            },
+            Movable::default(),
+            WeatherParticle,
+        ));
+    }
+}
+
+/// 落到地面以下的粒子销毁
+fn weather_despawn_system(
+    mut commands: Commands,
+    win_size: Res<WinSize>,
+    query: Query<(Entity, &Transform), With<WeatherParticle>>,
+) {
+    let ground_y = -(win_size.height / 2. - GROUND_IMG_SIZE.1);
+    for (entity, transform) in query.iter() {
+        if transform.translation.y < ground_y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// 重新开始时清除所有天气粒子
+fn weather_clear_system(mut commands: Commands, query: Query<Entity, With<WeatherParticle>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// 风力推动小鸟，同时有一个回到原位的拉力
+fn wind_system(
+    config: Res<ThemeConfig>,
+    state: Res<ThemeState>,
+    win_size: Res<WinSize>,
+    clock: Res<GameClock>,
+    mut query: Query<(&mut Velocity, &Transform), With<Player>>,
+) {
+    let wind = state.wind(&config);
+    // 与生成小鸟时的 x 坐标一致
+    let home_x = -win_size.width / 4. / 2.;
+    for (mut velocity, transform) in query.iter_mut() {
+        let offset = transform.translation.x - home_x;
+        let force = wind - offset * WIND_SPRING - velocity.x * WIND_DAMPING;
+        velocity.x += force * clock.step();
+    }
+}
@@ -0,0 +1,199 @@
+use bevy::{
+    app::AppExit,
+    core_pipeline::core_2d::Camera2d,
+    prelude::{
+        Color, Commands, CoreSet, Entity, EventReader, IVec2, IntoSystemConfig, NonSend,
+        OrthographicProjection, Plugin, Query, Res, ResMut, Resource, Sprite, SpriteBundle,
+        StartupSet, Transform, Vec2, With,
+    },
+    render::camera::ScalingMode,
+    window::{
+        MonitorSelection, PrimaryWindow, Window, WindowMode, WindowMoved, WindowPosition,
+        WindowResized,
+    },
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::LaunchOptions,
+    components::Letterbox,
+    constants::{LETTERBOX_SIZE, WINDOW_FILE_NAME, WINDOW_HEIGHT, WINDOW_WIDTH},
+    resource::{ScaleMode, Settings, WinSize},
+    storage::Storage,
+};
+
+/// 窗口布局插件
+///
+/// * 游戏区域的逻辑大小固定，缩放到任意大小的窗口中，多出来的区域用黑边遮住
+pub struct WindowLayoutPlugin;
+
+impl Plugin for WindowLayoutPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<WindowPlacement>()
+            .add_startup_system(spawn_letterbox_system.in_base_set(StartupSet::PostStartup))
+            .add_system(layout_system)
+            .add_system(track_placement_system)
+            .add_system(save_placement_system.in_base_set(CoreSet::Last));
+    }
+}
+
+/// 窗口的位置和大小，退出时保存，下次启动时恢复
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowPlacement {
+    /// 窗口左上角的物理像素坐标
+    pub position: Option<(i32, i32)>,
+    /// 窗口的逻辑大小
+    pub size: Option<(f32, f32)>,
+    /// 窗口所在显示器的编号
+    pub monitor: Option<usize>,
+}
+
+impl WindowPlacement {
+    /// 使用启动参数覆盖保存的位置
+    ///
+    /// * `--monitor <N>` 在第 N 个显示器上居中
+    /// * `--window-position <X>,<Y>` 指定窗口左上角的坐标
+    pub fn with_launch_options(mut self, options: &LaunchOptions) -> Self {
+        if let Some(monitor) = options.monitor {
+            self.monitor = Some(monitor);
+            self.position = None;
+        }
+        if let Some(position) = options.window_position {
+            self.position = Some(position);
+        }
+        self
+    }
+
+    /// 窗口的初始位置，没有记录时在显示器上居中
+    pub fn window_position(&self) -> WindowPosition {
+        match (self.position, self.monitor) {
+            (Some((x, y)), _) => WindowPosition::At(IVec2::new(x, y)),
+            (None, Some(index)) => WindowPosition::Centered(MonitorSelection::Index(index)),
+            (None, None) => WindowPosition::Centered(MonitorSelection::Primary),
+        }
+    }
+
+    /// 窗口的初始大小
+    pub fn resolution(&self) -> (f32, f32) {
+        self.size.unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT))
+    }
+}
+
+/// 生成游戏区域四周的黑边
+fn spawn_letterbox_system(mut commands: Commands, win_size: Res<WinSize>) {
+    let half_size = LETTERBOX_SIZE / 2.;
+    let (half_w, half_h) = (win_size.width / 2., win_size.height / 2.);
+    let bars = [
+        Vec2::new(-(half_w + half_size), 0.),
+        Vec2::new(half_w + half_size, 0.),
+        Vec2::new(0., half_h + half_size),
+        Vec2::new(0., -(half_h + half_size)),
+    ];
+    for position in bars {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::splat(LETTERBOX_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: position.extend(50.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Letterbox,
+        ));
+    }
+}
+
+/// 根据窗口大小计算缩放比例，并更新相机投影
+fn layout_system(
+    settings: Res<Settings>,
+    mut win_size: ResMut<WinSize>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut projections: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    // 按物理像素计算，整数缩放时每个游戏像素对应整数个屏幕像素
+    let physical_scale = (window.physical_width() as f32 / win_size.width)
+        .min(window.physical_height() as f32 / win_size.height);
+    let physical_scale = match settings.scale_mode {
+        ScaleMode::Integer if physical_scale >= 1. => physical_scale.floor(),
+        _ => physical_scale,
+    };
+    let scale = physical_scale / window.scale_factor() as f32;
+    if scale <= 0. || scale == win_size.scale {
+        return;
+    }
+
+    win_size.scale = scale;
+    for mut projection in projections.iter_mut() {
+        projection.scaling_mode = ScalingMode::WindowSize(scale);
+    }
+}
+
+/// 窗口当前所在显示器的编号，和 `MonitorSelection::Index` 使用相同的顺序
+fn current_monitor(winit_windows: &WinitWindows, entity: Entity) -> Option<usize> {
+    let window = winit_windows.get_window(entity)?;
+    let current = window.current_monitor()?;
+    window
+        .available_monitors()
+        .position(|monitor| monitor == current)
+}
+
+/// 记录窗口的位置、大小和所在的显示器，全屏时不记录
+fn track_placement_system(
+    mut moved_events: EventReader<WindowMoved>,
+    mut resized_events: EventReader<WindowResized>,
+    windows: Query<(Entity, &Window), With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut placement: ResMut<WindowPlacement>,
+) {
+    let moved = moved_events.iter().last().map(|event| event.position);
+    let resized = resized_events
+        .iter()
+        .last()
+        .map(|event| (event.width, event.height));
+    let Ok((entity, window)) = windows.get_single() else {
+        return;
+    };
+    if window.mode != WindowMode::Windowed {
+        return;
+    }
+    if let Some(position) = moved {
+        placement.position = Some((position.x, position.y));
+        placement.monitor = current_monitor(&winit_windows, entity).or(placement.monitor);
+    }
+    if let Some(size) = resized {
+        placement.size = Some(size);
+    }
+}
+
+/// 退出时保存窗口的位置和大小
+///
+/// * 保存前重新查询窗口所在的显示器，窗口没有移动过时也能记录下来
+fn save_placement_system(
+    mut exit_events: EventReader<AppExit>,
+    storage: Res<Storage>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    winit_windows: NonSend<WinitWindows>,
+    mut placement: ResMut<WindowPlacement>,
+) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    if let Some(monitor) = windows
+        .get_single()
+        .ok()
+        .and_then(|entity| current_monitor(&winit_windows, entity))
+    {
+        placement.monitor = Some(monitor);
+    }
+    storage.save(WINDOW_FILE_NAME, &*placement);
+}
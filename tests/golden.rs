@@ -0,0 +1,76 @@
+//! 回放回归测试
+//!
+//! * tests/golden 下每个文件保存一局的输入和期望的结果（得分、撞击帧数、轨迹哈希）
+//! * 修改了玩法（移动、重力、管道生成等），确认新的结果正确后，
+//!   使用 `UPDATE_GOLDEN=1 cargo test --test golden` 更新期望的结果
+
+use std::{fs, path::PathBuf};
+
+use flappy_bird_bevy::{
+    constants::REPLAY_MAX_FRAMES,
+    replay::{play, Replay, ReplayResult},
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct Golden {
+    replay: Replay,
+    result: ReplayResult,
+}
+
+fn golden_files() -> Vec<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn golden_replays() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let files = golden_files();
+    assert!(!files.is_empty());
+
+    let mut failures = vec![];
+    for path in files {
+        let mut golden: Golden = ron::from_str(&fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|err| panic!("{} 格式错误: {}", path.display(), err));
+        let result = play(&golden.replay, REPLAY_MAX_FRAMES);
+        if result == golden.result {
+            continue;
+        }
+        if update {
+            golden.result = result;
+            let config = PrettyConfig::default().compact_arrays(true);
+            let content = ron::ser::to_string_pretty(&golden, config).unwrap();
+            fs::write(&path, content + "\n").unwrap();
+        } else {
+            failures.push(format!(
+                "{}:\n  期望 {:?}\n  实际 {:?}",
+                path.display(),
+                golden.result,
+                result
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "回放结果发生变化，确认玩法修改正确后使用 UPDATE_GOLDEN=1 更新:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn replay_is_deterministic() {
+    let path = &golden_files()[0];
+    let golden: Golden = ron::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(
+        play(&golden.replay, REPLAY_MAX_FRAMES),
+        play(&golden.replay, REPLAY_MAX_FRAMES)
+    );
+}
@@ -0,0 +1,212 @@
+//! 排行榜客户端测试
+//!
+//! * 每个测试启动一个 `leaderboard_server` 模拟服务器，不需要真实的服务
+
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, ChildStdout, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use flappy_bird_bevy::{
+    constants::START_FLAPS,
+    leaderboard::{
+        fetch_top, submit, Leaderboard, LeaderboardConfig, LeaderboardPlugin, RequestError,
+        ScoreRecord, SignedRecord,
+    },
+    replay::{Replay, ReplayPlugin, ReplayResult},
+    simulation::Simulation,
+    state::GameState,
+};
+use serde::Deserialize;
+
+/// 测试使用的签名密钥
+const KEY: &str = "test-key";
+
+/// 在子进程中运行的模拟服务器，测试结束时关闭
+struct MockServer {
+    child: Child,
+    /// 保持管道打开，否则服务器输出日志时会失败
+    _stdout: BufReader<ChildStdout>,
+    address: String,
+}
+
+impl MockServer {
+    fn start(port: u16, args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_leaderboard_server"))
+            .args(["--port", &port.to_string(), "--key", KEY])
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // 第一行输出监听的地址
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let address = line.split_whitespace().last().unwrap().to_owned();
+        Self {
+            child,
+            _stdout: stdout,
+            address,
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn config(address: &str) -> LeaderboardConfig {
+    LeaderboardConfig {
+        enabled: true,
+        address: address.to_owned(),
+        player: "TESTER".to_owned(),
+        key: KEY.to_owned(),
+        top: 5,
+        timeout_seconds: 2.,
+        retry_seconds: 0.1,
+        max_retry_seconds: 0.4,
+    }
+}
+
+#[derive(Deserialize)]
+struct Golden {
+    replay: Replay,
+    result: ReplayResult,
+}
+
+/// 用 tests/golden 中的一局生成可以通过验证的记录
+fn record(player: &str, golden: &str) -> ScoreRecord {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(golden)
+        .with_extension("ron");
+    let golden: Golden = ron::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    ScoreRecord {
+        player: player.to_owned(),
+        score: golden.result.score,
+        seed: golden.replay.seed,
+        difficulty: golden.replay.difficulty,
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        death_frame: golden.result.death_frame.unwrap(),
+        replay: Some(golden.replay),
+    }
+}
+
+#[test]
+fn submitted_scores_are_ranked() {
+    let server = MockServer::start(0, &[]);
+    let config = config(&server.address);
+
+    for (player, golden, rank) in [
+        ("A", "first_pipe", 1),
+        ("B", "five_pipes", 1),
+        ("C", "idle", 3),
+    ] {
+        let signed = SignedRecord::new(record(player, golden), &config.key);
+        assert_eq!(submit(&config, &signed), Ok(rank));
+    }
+    let top = fetch_top(&config, 2).unwrap();
+    let top: Vec<_> = top
+        .iter()
+        .map(|entry| (entry.player.as_str(), entry.score))
+        .collect();
+    assert_eq!(top, [("B", 5), ("A", 1)]);
+}
+
+#[test]
+fn forged_scores_are_rejected() {
+    let server = MockServer::start(0, &[]);
+    let config = config(&server.address);
+
+    let rejected = |record: ScoreRecord, key: &str| {
+        matches!(
+            submit(&config, &SignedRecord::new(record, key)),
+            Err(RequestError::Rejected(_))
+        )
+    };
+
+    let mut forged = SignedRecord::new(record("A", "first_pipe"), &config.key);
+    forged.record.score = 99;
+    assert!(matches!(
+        submit(&config, &forged),
+        Err(RequestError::Rejected(_))
+    ));
+    assert!(rejected(record("A", "first_pipe"), "guess"));
+    // 重新模拟回放，开始后不再飞行的一局得分是 0
+    let base = record("A", "first_pipe");
+    let replayed = ScoreRecord {
+        replay: Some(Replay {
+            flaps: START_FLAPS.to_vec(),
+            ..base.replay.clone().unwrap()
+        }),
+        ..base.clone()
+    };
+    assert!(rejected(replayed, &config.key));
+    // 撞击帧数不一致，或者不带回放
+    let late_death = ScoreRecord {
+        death_frame: base.death_frame + 1,
+        ..base.clone()
+    };
+    assert!(rejected(late_death, &config.key));
+    let without_replay = ScoreRecord {
+        replay: None,
+        ..base
+    };
+    assert!(rejected(without_replay, &config.key));
+    assert!(fetch_top(&config, 5).unwrap().is_empty());
+}
+
+#[test]
+fn offline_scores_are_queued_and_retried() {
+    // 先占用一个空闲端口再释放，此时没有服务器监听
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{}", port);
+
+    let mut sim = Simulation::new(1);
+    sim.app()
+        .add_plugin(ReplayPlugin)
+        .add_plugin(LeaderboardPlugin)
+        .insert_resource(config(&address));
+    sim.start_run();
+    sim.run_until(600, |sim| sim.state() == GameState::GameOver)
+        .expect("没有在 600 帧内结束");
+    let queued = |sim: &mut Simulation| sim.app().world.resource::<Leaderboard>().queue().len();
+    assert_eq!(queued(&mut sim), 1);
+
+    // 离线时记录保留在队列中
+    wait_until(&mut sim, |sim| {
+        !sim.app().world.resource::<Leaderboard>().busy()
+    });
+    assert_eq!(queued(&mut sim), 1);
+    assert!(!sim.app().world.resource::<Leaderboard>().online);
+
+    // 服务器恢复后，第一次请求仍然失败，之后重试成功
+    let server = MockServer::start(port, &["--fail", "1"]);
+    wait_until(&mut sim, |sim| queued(sim) == 0);
+    let top = fetch_top(&config(&server.address), 5).unwrap();
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].player, "TESTER");
+    assert_eq!(top[0].score, 0);
+}
+
+/// 推进游戏直到满足条件，后台请求使用真实的时间
+fn wait_until(sim: &mut Simulation, mut condition: impl FnMut(&mut Simulation) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !condition(sim) {
+        assert!(Instant::now() < deadline, "等待超时");
+        sim.step();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,279 @@
+use bevy::{
+    prelude::{Entity, Events, KeyCode},
+    window::WindowFocused,
+};
+use flappy_bird_bevy::{
+    bot::BotPolicy,
+    constants::{BIRD_IMG_SIZE, GROUND_IMG_SIZE, REPLAY_MAX_FRAMES, WINDOW_HEIGHT},
+    events::{CrashCause, RunRecorded},
+    pause::PauseState,
+    practice::PracticePlugin,
+    replay::{self, Claim, ReplayPlugin},
+    resource::Records,
+    simulation::Simulation,
+    state::GameState,
+};
+
+/// 启用练习工具的模拟
+fn practice_sim(seed: u64) -> Simulation {
+    let mut sim = Simulation::new(seed);
+    sim.app().add_plugin(PracticePlugin);
+    sim
+}
+
+#[test]
+fn no_input_falls_to_game_over() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+    assert_eq!(sim.state(), GameState::InGame);
+
+    let frames = sim.run_until(600, |sim| sim.state() == GameState::GameOver);
+    assert!(frames.is_some(), "没有在 600 帧内结束");
+    // 撞到最近的管道后落到地面
+    assert_eq!(sim.run_stats().cause, Some(CrashCause::Pipe));
+    assert_eq!(sim.run_stats().score, 0);
+    let ground = -(WINDOW_HEIGHT / 2. - GROUND_IMG_SIZE.1);
+    assert!(sim.bird().unwrap().y <= ground + BIRD_IMG_SIZE.0 / 2.);
+}
+
+#[test]
+fn following_the_gap_scores_one() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+
+    for _ in 0..2000 {
+        if (sim.score() >= 1 && sim.run_stats().score >= 1) || sim.state() != GameState::InGame {
+            break;
+        }
+        BotPolicy::FollowGap.step(&mut sim);
+    }
+    assert_eq!(sim.state(), GameState::InGame);
+    assert_eq!(sim.score(), 1);
+    assert_eq!(sim.run_stats().score, 1);
+}
+
+#[test]
+fn same_seed_and_input_replays_identically() {
+    let trajectory = |seed| {
+        let mut sim = Simulation::new(seed);
+        sim.start_run();
+        let mut positions = vec![];
+        for frame in 0..300 {
+            if frame % 20 == 0 {
+                sim.flap();
+            } else {
+                sim.step();
+            }
+            positions.push((sim.bird(), sim.obstacles()));
+        }
+        positions
+    };
+    assert_eq!(trajectory(7), trajectory(7));
+}
+
+#[test]
+fn pause_key_freezes_bird() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+    sim.tap(KeyCode::P);
+    let bird = sim.bird();
+    sim.step_n(60);
+
+    assert_eq!(sim.pause_state(), PauseState::Paused);
+    assert_eq!(sim.state(), GameState::InGame);
+    assert_eq!(sim.bird(), bird);
+}
+
+#[test]
+fn losing_focus_during_resume_countdown_pauses_again() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+    sim.tap(KeyCode::P);
+    sim.tap(KeyCode::R);
+    sim.step();
+    assert_eq!(sim.pause_state(), PauseState::Resuming);
+
+    sim.app().world.send_event(WindowFocused {
+        window: Entity::PLACEHOLDER,
+        focused: false,
+    });
+    sim.step_n(2);
+    assert_eq!(sim.pause_state(), PauseState::Paused);
+}
+
+#[test]
+fn every_run_is_recorded_and_replays_on_its_own() {
+    let mut sim = Simulation::new(1);
+    sim.app().add_plugin(ReplayPlugin);
+    // 跟随管道飞行一段时间后不再飞行，返回这一局的录制和得分
+    let play = |sim: &mut Simulation| {
+        sim.run_until(400, |sim| {
+            BotPolicy::FollowGap.step(sim);
+            sim.state() != GameState::InGame
+        });
+        sim.run_until(600, |sim| sim.state() == GameState::GameOver)
+            .expect("没有在 600 帧内结束");
+        let events = sim.app().world.resource::<Events<RunRecorded>>();
+        let run = events.iter_current_update_events().last().unwrap();
+        (run.replay.clone(), run.death_frame, sim.run_stats().score)
+    };
+
+    sim.start_run();
+    let first = play(&mut sim);
+    // 结算面板滑入后按空格重新开始，在准备状态停留一会儿，第二局中暂停一次
+    sim.run_until(600, |sim| {
+        sim.flap();
+        sim.state() != GameState::GameOver
+    })
+    .expect("没有重新开始");
+    sim.step_n(17);
+    sim.flap();
+    sim.step_n(10);
+    sim.tap(KeyCode::P);
+    sim.step_n(30);
+    sim.tap(KeyCode::R);
+    sim.run_until(600, |sim| sim.pause_state() == PauseState::Running)
+        .expect("没有恢复");
+    let second = play(&mut sim);
+
+    assert_eq!(first.0.seed, 1);
+    assert_ne!(second.0.seed, 1);
+    for (replay, death_frame, score) in [first, second] {
+        assert!(score > 0);
+        let claimed = Claim { score, death_frame };
+        let verdict = replay::verify(&replay, claimed, REPLAY_MAX_FRAMES);
+        assert!(verdict.valid, "{:?}", verdict);
+    }
+}
+
+#[test]
+fn dying_right_after_a_point_does_not_affect_the_next_run() {
+    let mut sim = practice_sim(1);
+    sim.app().add_plugin(ReplayPlugin);
+    sim.start_run();
+    // 无敌模式下贴着地面穿过第一组管道，得分后关闭无敌，在生成下一组管道之前撞到地面
+    sim.tap(KeyCode::I);
+    sim.run_until(2000, |sim| {
+        sim.step();
+        sim.score() >= 1
+    })
+    .expect("没有得分");
+    sim.tap(KeyCode::I);
+    sim.run_until(600, |sim| sim.state() == GameState::GameOver)
+        .expect("没有结束");
+    assert_eq!(sim.run_stats().cause, Some(CrashCause::Ground));
+
+    sim.run_until(600, |sim| {
+        sim.flap();
+        sim.state() != GameState::GameOver
+    })
+    .expect("没有重新开始");
+    sim.run_until(400, |sim| {
+        BotPolicy::FollowGap.step(sim);
+        sim.state() != GameState::InGame
+    });
+    sim.run_until(600, |sim| sim.state() == GameState::GameOver)
+        .expect("没有在 600 帧内结束");
+    let score = sim.run_stats().score;
+    let events = sim.app().world.resource::<Events<RunRecorded>>();
+    let run = events.iter_current_update_events().last().unwrap();
+    let claimed = Claim {
+        score,
+        death_frame: run.death_frame,
+    };
+    let verdict = replay::verify(&run.replay, claimed, REPLAY_MAX_FRAMES);
+    assert!(verdict.valid, "{:?}", verdict);
+}
+
+#[test]
+fn flap_streak_resets_after_a_long_glide() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+    for _ in 0..3 {
+        sim.flap();
+        sim.step_n(20);
+    }
+    let streak = sim.run_stats().flap_streak;
+    assert!(streak >= 3);
+
+    sim.step_n(50);
+    sim.flap();
+    sim.step();
+    assert_eq!(sim.state(), GameState::InGame);
+    assert_eq!(sim.run_stats().flap_streak, 1);
+    assert_eq!(sim.run_stats().best_flap_streak, streak);
+}
+
+#[test]
+fn invincible_bird_survives() {
+    let mut sim = practice_sim(1);
+    sim.start_run();
+    sim.tap(KeyCode::I);
+    sim.step_n(1000);
+
+    assert_eq!(sim.state(), GameState::InGame);
+    assert!(sim.run_stats().practice);
+    let ground = -(WINDOW_HEIGHT / 2. - GROUND_IMG_SIZE.1);
+    assert!(sim.bird().unwrap().y >= ground);
+}
+
+#[test]
+fn practice_keys_need_practice_mode() {
+    let mut sim = Simulation::new(1);
+    sim.start_run();
+    sim.tap(KeyCode::I);
+    sim.tap(KeyCode::T);
+    assert_eq!(sim.score(), 0);
+
+    sim.run_until(600, |sim| sim.state() == GameState::GameOver)
+        .expect("没有在 600 帧内结束");
+    assert!(!sim.run_stats().practice);
+}
+
+#[test]
+fn frame_step_advances_one_frame_while_paused() {
+    let mut sim = practice_sim(1);
+    sim.start_run();
+    sim.tap(KeyCode::P);
+    sim.step();
+    let paused = sim.bird().unwrap();
+
+    sim.tap(KeyCode::Period);
+    let stepped = sim.bird().unwrap();
+    assert_ne!(stepped, paused);
+
+    sim.step_n(30);
+    assert_eq!(sim.pause_state(), PauseState::Paused);
+    assert_eq!(sim.bird().unwrap(), stepped);
+}
+
+#[test]
+fn practice_score_is_not_a_record() {
+    let mut sim = practice_sim(1);
+    sim.start_run();
+    sim.tap(KeyCode::T);
+    assert_eq!(sim.score(), 10);
+
+    let frames = sim.run_until(600, |sim| sim.state() == GameState::GameOver);
+    assert!(frames.is_some());
+    assert!(sim.run_stats().practice);
+    assert_eq!(sim.app().world.resource::<Records>().best, 0);
+}
+
+#[test]
+fn practice_score_saturates_instead_of_overflowing() {
+    let mut sim = practice_sim(1);
+    sim.start_run();
+    sim.tap(KeyCode::I);
+    for _ in 0..30 {
+        sim.tap(KeyCode::T);
+    }
+    assert_eq!(sim.score(), 250);
+
+    sim.run_until(3000, |sim| sim.score() == u8::MAX)
+        .expect("没有在 3000 帧内得到 255 分");
+    let passed = sim.run_stats().score;
+    sim.run_until(600, |sim| sim.run_stats().score > passed)
+        .expect("没有在 600 帧内通过下一组管道");
+    assert_eq!(sim.score(), u8::MAX);
+}
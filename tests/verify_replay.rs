@@ -0,0 +1,175 @@
+//! 回放验证工具测试
+//!
+//! * 用 tests/golden 中的回放运行 `verify_replay`，检查退出码和输出的结论
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use flappy_bird_bevy::{
+    leaderboard::{ScoreRecord, SignedRecord},
+    replay::{Replay, ReplayResult},
+};
+use serde::Deserialize;
+
+/// 测试使用的签名密钥
+const KEY: &str = "test-key";
+
+#[derive(Deserialize)]
+struct Golden {
+    replay: Replay,
+    result: ReplayResult,
+}
+
+fn golden() -> Golden {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/five_pipes.ron");
+    ron::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// 运行验证工具，返回退出码和输出的结论
+fn verify(args: &[&str]) -> (i32, serde_json::Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_verify_replay"))
+        .args(args)
+        .output()
+        .unwrap();
+    let verdict = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    (output.status.code().unwrap(), verdict)
+}
+
+/// 每个测试使用独立的临时目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("flappy_verify_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn path(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn replay_file_claims() {
+    let golden = golden();
+    let dir = temp_dir("replay");
+    let file = dir.join("run.ron");
+    golden.replay.save(&file).unwrap();
+    let score = golden.result.score.to_string();
+    let death_frame = golden.result.death_frame.unwrap().to_string();
+
+    let (code, verdict) = verify(&[
+        path(&file),
+        "--score",
+        &score,
+        "--death-frame",
+        &death_frame,
+    ]);
+    assert_eq!(code, 0);
+    assert_eq!(verdict["valid"], true);
+    assert_eq!(verdict["actual"]["score"], golden.result.score);
+    assert_eq!(
+        verdict["actual"]["trajectory_hash"],
+        golden.result.trajectory_hash
+    );
+
+    let (code, verdict) = verify(&[path(&file), "--score", "99", "--death-frame", &death_frame]);
+    assert_eq!(code, 1);
+    assert_eq!(verdict["valid"], false);
+    assert!(verdict["reason"].as_str().unwrap().contains("得分"));
+
+    let (code, verdict) = verify(&[path(&file), "--score", &score, "--death-frame", "1"]);
+    assert_eq!(code, 1);
+    assert!(verdict["reason"].as_str().unwrap().contains("撞击帧数"));
+
+    // 在最大帧数内没有撞击
+    let (code, verdict) = verify(&[
+        path(&file),
+        "--score",
+        "0",
+        "--death-frame",
+        &death_frame,
+        "--max-frames",
+        "100",
+    ]);
+    assert_eq!(code, 1);
+    assert!(verdict["reason"].as_str().unwrap().contains("没有撞击"));
+
+    // 缺少声明的得分或撞击帧数，或者文件不存在
+    assert_eq!(verify(&[path(&file)]).0, 2);
+    assert_eq!(verify(&[path(&file), "--score", &score]).0, 2);
+    assert_eq!(
+        verify(&[
+            path(&dir.join("missing.ron")),
+            "--score",
+            "1",
+            "--death-frame",
+            "1"
+        ])
+        .0,
+        2
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn signed_record_claims() {
+    let golden = golden();
+    let dir = temp_dir("record");
+    let write = |name: &str, signed: &SignedRecord| {
+        let file = dir.join(name);
+        fs::write(&file, serde_json::to_string(signed).unwrap()).unwrap();
+        file
+    };
+    let record = ScoreRecord {
+        player: "TESTER".to_owned(),
+        score: golden.result.score,
+        seed: golden.replay.seed,
+        difficulty: golden.replay.difficulty,
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        replay: Some(golden.replay.clone()),
+        death_frame: golden.result.death_frame.unwrap(),
+    };
+
+    let file = write("valid.json", &SignedRecord::new(record.clone(), KEY));
+    assert_eq!(verify(&[path(&file), "--key", KEY]).0, 0);
+    assert_eq!(verify(&[path(&file), "--key", "other"]).0, 1);
+    // 签名记录必须指定密钥，使用记录中的撞击帧数
+    assert_eq!(verify(&[path(&file)]).0, 2);
+    assert_eq!(
+        verify(&[path(&file), "--key", KEY, "--death-frame", "1"]).0,
+        2
+    );
+
+    // 修改得分后重新签名，签名有效但模拟的得分不一致
+    let forged = ScoreRecord {
+        score: 50,
+        ..record.clone()
+    };
+    let file = write("forged.json", &SignedRecord::new(forged, KEY));
+    let (code, verdict) = verify(&[path(&file), "--key", KEY]);
+    assert_eq!(code, 1);
+    assert!(verdict["reason"].as_str().unwrap().contains("得分"));
+
+    let late_death = ScoreRecord {
+        death_frame: record.death_frame + 1,
+        ..record.clone()
+    };
+    let file = write("late_death.json", &SignedRecord::new(late_death, KEY));
+    let (code, verdict) = verify(&[path(&file), "--key", KEY]);
+    assert_eq!(code, 1);
+    assert!(verdict["reason"].as_str().unwrap().contains("撞击帧数"));
+
+    let without_replay = ScoreRecord {
+        replay: None,
+        ..record
+    };
+    let file = write(
+        "without_replay.json",
+        &SignedRecord::new(without_replay, KEY),
+    );
+    assert_eq!(verify(&[path(&file), "--key", KEY]).0, 1);
+
+    fs::remove_dir_all(dir).unwrap();
+}